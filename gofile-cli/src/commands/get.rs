@@ -1,20 +1,56 @@
 use crate::Config;
-use crate::util::parse_page_url;
+use crate::filter::Filter;
+use crate::filter::FilterOptions;
+use crate::progress::Progress;
+use crate::progress::spawn_progress_bar_ticker;
+use crate::util::UniqueNames;
+use crate::util::is_id;
+use crate::util::number_file_name;
+use crate::util::parse_url_list;
+use crate::util::sanitize_file_name;
 use anyhow::Context;
 use anyhow::bail;
 use anyhow::ensure;
-use md5::Digest;
-use md5::Md5;
-use std::io::Write;
+use bytesize::ByteSize;
+use futures_util::StreamExt;
+use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
-use std::time::Duration;
-use url::Url;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// What to do when a file already exists.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum OnExisting {
+    /// Skip the file
+    #[default]
+    Skip,
+
+    /// Check the file against its md5 hash and size, and download it again if it does not match
+    Verify,
+
+    /// Download the file again
+    Overwrite,
+
+    /// Download the file to a new name
+    Rename,
+}
 
 #[derive(Debug, clap::Parser)]
-#[command(about = "Download a file or folder from a https://gofile.io link")]
+#[command(about = "Download files or folders from https://gofile.io links")]
 pub struct Options {
-    pub url: String,
+    #[arg(
+        help = "The folder links or file download links to download",
+        required_unless_present = "input_file"
+    )]
+    pub urls: Vec<String>,
+
+    #[arg(
+        long = "input-file",
+        short = 'i',
+        help = "A file with links to download, one per line. Lines starting with \"#\" are ignored. Use \"-\" to read from stdin"
+    )]
+    pub input_file: Option<PathBuf>,
 
     #[arg(
         long = "output",
@@ -24,7 +60,10 @@ pub struct Options {
     )]
     pub output: PathBuf,
 
-    #[arg(help = "If specified, only download the child entry with this id")]
+    #[arg(
+        long = "child-id",
+        help = "If specified, only download the child entry with this id. Only allowed with a single link"
+    )]
     pub child_id: Option<String>,
 
     #[arg(
@@ -32,6 +71,134 @@ pub struct Options {
         help = "Do not append the file or folder name to the output path"
     )]
     pub no_append_name: bool,
+
+    #[arg(long = "password", help = "The password of the folder")]
+    pub password: Option<String>,
+
+    #[arg(
+        long = "resume",
+        help = "Resume interrupted downloads from their \".part\" files",
+        default_value_t = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        action = clap::ArgAction::Set,
+    )]
+    pub resume: bool,
+
+    #[arg(
+        long = "connections",
+        help = "The number of connections to download each file with",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    pub connections: u32,
+
+    #[arg(
+        long = "jobs",
+        short = 'j',
+        help = "The number of files to download at the same time",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    pub jobs: u32,
+
+    #[arg(
+        long = "max-depth",
+        help = "The maximum depth of subfolders to descend into, where 0 only downloads the top folder"
+    )]
+    pub max_depth: Option<usize>,
+
+    #[arg(
+        long = "flat",
+        help = "Download the files of all subfolders into the output folder, without recreating subfolders"
+    )]
+    pub flat: bool,
+
+    #[arg(
+        long = "raw-names",
+        help = "Use remote file names as they are, without making them safe or unique for the local file system"
+    )]
+    pub raw_names: bool,
+
+    #[arg(
+        long = "on-existing",
+        help = "What to do when a file already exists",
+        default_value_t = Default::default(),
+        value_enum,
+    )]
+    pub on_existing: OnExisting,
+
+    #[command(flatten)]
+    pub filter: FilterOptions,
+
+    #[arg(
+        long = "no-preserve-times",
+        help = "Do not set the modification times of downloaded files and folders to their remote modification times"
+    )]
+    pub no_preserve_times: bool,
+
+    #[arg(
+        long = "dry-run",
+        help = "List what would be downloaded, skipped or overwritten, without downloading anything or creating folders"
+    )]
+    pub dry_run: bool,
+}
+
+impl Options {
+    /// Get the local name to use for a remote name.
+    fn local_name(&self, name: &str) -> String {
+        if self.raw_names {
+            name.to_string()
+        } else {
+            sanitize_file_name(name)
+        }
+    }
+}
+
+/// The result of downloading a page child.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Outcome {
+    Downloaded,
+    Skipped,
+}
+
+/// Set the modification time of a file or folder.
+async fn set_mod_time(path: PathBuf, mod_time: SystemTime) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        filetime::set_file_mtime(&path, filetime::FileTime::from_system_time(mod_time))
+            .with_context(|| format!("failed to set modification time of \"{}\"", path.display()))
+    })
+    .await?
+}
+
+/// Count a skipped file as done in the total progress bar.
+fn skip_progress(progress: &Progress, child: &gofile::PageChild) {
+    if let (Some(total_progress_bar), Some(size)) =
+        (progress.total_progress_bar.as_ref(), child.size)
+    {
+        total_progress_bar.inc(size);
+    }
+}
+
+/// Find a path that does not exist yet by adding a number to the file name.
+async fn next_free_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .context("missing file name")?
+        .to_string_lossy()
+        .into_owned();
+
+    let mut n = 1;
+    loop {
+        let candidate = path.with_file_name(number_file_name(&name, n));
+        let metadata = try_metadata(&candidate)
+            .await
+            .with_context(|| format!("failed to get metadata for \"{}\"", candidate.display()))?;
+        if metadata.is_none() {
+            return Ok(candidate);
+        }
+        n += 1;
+    }
 }
 
 async fn try_metadata<P>(path: P) -> std::io::Result<Option<std::fs::Metadata>>
@@ -47,140 +214,592 @@ where
 
 async fn download_page_child(
     client: &gofile::Client,
+    options: &Options,
+    progress: &Progress,
     child: &gofile::PageChild,
     out_path: PathBuf,
-) -> anyhow::Result<()> {
-    let expected_md5_hash =
-        base16ct::lower::decode_vec(child.md5.as_ref().context("missing md5 hash")?)?;
+) -> anyhow::Result<Outcome> {
+    // Dry runs print their plan to stdout, where it is not mixed with progress bars.
+    let report = |line: String| {
+        if options.dry_run {
+            println!("{line}");
+        } else {
+            progress.println(&line);
+        }
+    };
 
+    let mut out_path = out_path;
     let metadata = try_metadata(&out_path)
         .await
         .with_context(|| format!("failed to get metadata for \"{}\"", out_path.display()))?;
-    match metadata {
-        Some(metadata) if metadata.is_dir() => {
+    let will_overwrite = metadata.is_some()
+        && matches!(
+            options.on_existing,
+            OnExisting::Verify | OnExisting::Overwrite
+        );
+    match (metadata, options.on_existing) {
+        (None, _) => {}
+        (Some(_metadata), OnExisting::Rename) => {
+            out_path = next_free_path(&out_path).await?;
+        }
+        (Some(metadata), _) if metadata.is_dir() => {
             bail!("output path \"{}\" is a folder", out_path.display());
         }
-        Some(_metadata) => {
-            // TODO: Consider validating md5 here and adding overwrite options to the cli.
-            eprintln!("file exists, skipping...");
-            return Ok(());
+        (Some(_metadata), OnExisting::Skip) => {
+            report(format!("\"{}\" exists, skipping...", out_path.display()));
+            skip_progress(progress, child);
+            return Ok(Outcome::Skipped);
         }
-        None => {}
+        (Some(_metadata), OnExisting::Verify) if child.md5.is_none() && child.size.is_none() => {
+            report(format!(
+                "\"{}\" exists but cannot be verified, since its md5 hash and size are unknown, skipping...",
+                out_path.display()
+            ));
+            skip_progress(progress, child);
+            return Ok(Outcome::Skipped);
+        }
+        (Some(_metadata), OnExisting::Verify) => {
+            match gofile::verify_file(child, &out_path).await {
+                Ok(()) => {
+                    report(format!(
+                        "\"{}\" exists and is valid, skipping...",
+                        out_path.display()
+                    ));
+                    skip_progress(progress, child);
+                    return Ok(Outcome::Skipped);
+                }
+                Err(
+                    error
+                    @ (gofile::Error::Md5Mismatch { .. } | gofile::Error::SizeMismatch { .. }),
+                ) => {
+                    report(format!(
+                        "\"{}\" exists but does not match ({error}), downloading again...",
+                        out_path.display()
+                    ));
+                }
+                Err(error) => {
+                    return Err(error)
+                        .with_context(|| format!("failed to verify \"{}\"", out_path.display()));
+                }
+            }
+        }
+        (Some(_metadata), OnExisting::Overwrite) => {}
+    }
+
+    if options.dry_run {
+        let action = if will_overwrite {
+            "overwrite"
+        } else {
+            "download"
+        };
+        let size = child
+            .size
+            .map(|size| ByteSize(size).to_string())
+            .unwrap_or_else(|| "unknown size".into());
+        println!("Would {action} \"{}\" ({size})", out_path.display());
+        return Ok(Outcome::Downloaded);
     }
-    let out_path_temp = out_path.with_added_extension("part");
 
-    let progress_bar = indicatif::ProgressBar::new(child.size.context("missing file size")?);
-    let progress_bar_style_template = "[Time = {elapsed_precise} | ETA = {eta_precise} | Speed = {bytes_per_sec}] {wide_bar} {bytes}/{total_bytes}";
-    let progress_bar_style = indicatif::ProgressStyle::default_bar()
-        .template(progress_bar_style_template)
-        .expect("invalid progress bar style template");
-    progress_bar.set_style(progress_bar_style);
-    let progress_bar_tick_handle = {
+    // Files from download links have no known size.
+    let progress_bar = progress.add_file_progress_bar(child.size, &child.name);
+    let progress_bar_tick_handle = spawn_progress_bar_ticker(progress_bar.clone());
+
+    let download_options = {
         let progress_bar = progress_bar.clone();
-        tokio::spawn(async move {
-            while !progress_bar.is_finished() {
-                progress_bar.tick();
-                tokio::time::sleep(Duration::from_millis(1_000)).await;
-            }
-        })
+        let total_progress_bar = progress.total_progress_bar.clone();
+        gofile::DownloadOptions::new()
+            .resume(options.resume)
+            .connections(options.connections)
+            .progress(move |n| {
+                progress_bar.inc(n);
+                if let Some(total_progress_bar) = total_progress_bar.as_ref() {
+                    total_progress_bar.inc(n);
+                }
+            })
     };
+    let result = client
+        .download_to_path(child, &out_path, &download_options)
+        .await;
+    progress_bar.finish();
+    progress_bar_tick_handle.await?;
+    progress.multi_progress.remove(&progress_bar);
+    result?;
 
-    let token = client.get_token()?;
-    let download_url = child.link.as_ref().context("missing download url")?;
-    let mut response = client
-        .client
-        .get(download_url)
-        .header("Cookie", format!("accountToken={token}"))
-        .send()
-        .await?
-        .error_for_status()?;
-    let handle = tokio::runtime::Handle::current();
-    tokio::task::spawn_blocking(move || {
-        let mut hasher = Md5::new();
+    if !options.no_preserve_times {
+        set_mod_time(out_path, child.mod_time.into()).await?;
+    }
 
-        let mut out_file = std::fs::File::options()
-            .create(true)
-            .write(true)
-            .truncate(false)
-            .open(&out_path_temp)?;
-        out_file.try_lock()?;
-        out_file.set_len(0)?;
+    Ok(Outcome::Downloaded)
+}
 
-        while let Some(chunk) = handle.block_on(response.chunk())? {
-            out_file.write_all(&chunk)?;
-            hasher.update(&chunk);
+/// Get the path of a walk entry relative to the folder being walked, using "/" as the separator.
+fn remote_path(entry: &gofile::WalkEntry) -> String {
+    let mut path = entry.path.join("/");
+    if !path.is_empty() {
+        path.push('/');
+    }
+    path.push_str(&entry.child.name);
+    path
+}
 
-            let chunk_len_u64 = u64::try_from(chunk.len())?;
-            progress_bar.inc(chunk_len_u64);
+/// Set the modification time of the folder made for a link.
+///
+/// The walk does not include the folder itself, so its time is fetched if not given.
+async fn set_root_mod_time(
+    client: &gofile::Client,
+    options: &Options,
+    code: &str,
+    mod_time: Option<SystemTime>,
+    out_dir: PathBuf,
+) -> anyhow::Result<()> {
+    let mod_time = match mod_time {
+        Some(mod_time) => mod_time,
+        None => {
+            let query = gofile::PageQuery {
+                page_size: 1,
+                password: options.password.clone(),
+                ..Default::default()
+            };
+            let page = client
+                .get_page_number(code, &query, 1)
+                .await
+                .context("failed to get the modification time of the folder")?;
+            page.mod_time.into()
         }
+    };
 
-        out_file.flush()?;
-        out_file.sync_all()?;
+    set_mod_time(out_dir, mod_time).await
+}
 
-        let actual_md5_hash = hasher.finalize();
-        let actual_md5_hash_slice = actual_md5_hash.as_slice();
-        ensure!(
-            actual_md5_hash_slice == expected_md5_hash,
-            "md5 hash mismatch"
-        );
+/// Download a folder and all of its subfolders into the given folder.
+///
+/// `mod_time` is the modification time of the folder itself, which is fetched if not given.
+/// It is only set if the output folder was made for the link.
+async fn download_folder(
+    client: &gofile::Client,
+    options: &Options,
+    code: &str,
+    mod_time: Option<SystemTime>,
+    out_dir: PathBuf,
+) -> anyhow::Result<()> {
+    if !options.dry_run {
+        tokio::fs::create_dir_all(&out_dir).await?;
+    }
 
-        std::fs::rename(out_path_temp, out_path)?;
-        out_file.unlock()?;
-        progress_bar.finish();
+    let filter = Arc::new(Filter::new(&options.filter)?);
+    let walk_options = gofile::WalkOptions {
+        max_depth: options.max_depth,
+        query: gofile::PageQuery {
+            password: options.password.clone(),
+            ..Default::default()
+        },
+        descend: {
+            let filter = filter.clone();
+            Some(Arc::new(move |entry: &gofile::WalkEntry| {
+                filter.matches_folder(&remote_path(entry))
+            }))
+        },
+        ..Default::default()
+    };
+    let mut entries = std::pin::pin!(client.walk_with(code, walk_options));
+    let mut names = UniqueNames::default();
+    let mut folder_paths: HashMap<String, PathBuf> = HashMap::new();
+    let mut folder_mod_times = Vec::new();
+    let mut files = Vec::new();
+    let mut failed = 0;
+    let mut is_first = true;
+    while let Some(entry) = entries.next().await {
+        let entry = match entry {
+            Ok(entry) => entry,
+            // The first entry is only an error if the top folder could not be listed.
+            Err(error) if is_first => return Err(error).context("failed to get page"),
+            Err(error) => {
+                eprintln!("failed to list folder: {error:?}");
+                failed += 1;
+                continue;
+            }
+        };
+        is_first = false;
 
-        anyhow::Ok(())
-    })
-    .await??;
+        let is_match = match entry.child.kind {
+            gofile::PageChildKind::File => filter.matches_file(&remote_path(&entry), &entry.child),
+            gofile::PageChildKind::Folder => filter.matches_folder(&remote_path(&entry)),
+        };
+        if !is_match {
+            continue;
+        }
 
-    progress_bar_tick_handle.await?;
+        // Parent folders are always listed before their children.
+        let parent_dir = if options.flat || entry.depth == 0 {
+            &out_dir
+        } else {
+            folder_paths
+                .get(&entry.parent_id)
+                .context("missing parent folder path")?
+        };
+        // Only files, and folders that are listed and not flattened, take up a local name.
+        let is_local_folder =
+            entry.child.kind == gofile::PageChildKind::Folder && entry.descended && !options.flat;
+        if entry.child.kind == gofile::PageChildKind::Folder && !is_local_folder {
+            continue;
+        }
+        let mut name = options.local_name(&entry.child.name);
+        if !options.raw_names {
+            name = names.claim(parent_dir, &name);
+        }
+        let out_path = parent_dir.join(name);
+
+        match entry.child.kind {
+            gofile::PageChildKind::File => files.push((entry.child, out_path)),
+            gofile::PageChildKind::Folder => {
+                if !options.dry_run {
+                    tokio::fs::create_dir_all(&out_path)
+                        .await
+                        .with_context(|| {
+                            format!("failed to create folder \"{}\"", out_path.display())
+                        })?;
+                }
+                folder_mod_times.push((out_path.clone(), entry.child.mod_time));
+                folder_paths.insert(entry.child.id.clone(), out_path);
+            }
+        }
+    }
+
+    let total_size = files.iter().filter_map(|(child, _)| child.size).sum();
+    let progress = Progress::new((!options.dry_run).then_some(total_size));
+    let total_progress_bar_tick_handle = progress
+        .total_progress_bar
+        .clone()
+        .map(spawn_progress_bar_ticker);
+
+    // Keep the listing in order for dry runs.
+    let jobs = if options.dry_run {
+        1
+    } else {
+        usize::try_from(options.jobs)?
+    };
+    let outcomes: Vec<(Option<Outcome>, u64)> = futures_util::stream::iter(files)
+        .map(|(child, out_path)| {
+            let progress = &progress;
+            async move {
+                let size = child.size.unwrap_or(0);
+                match download_page_child(client, options, progress, &child, out_path).await {
+                    Ok(outcome) => (Some(outcome), size),
+                    Err(error) => {
+                        progress
+                            .println(&format!("failed to download \"{}\": {error:?}", child.name));
+                        (None, size)
+                    }
+                }
+            }
+        })
+        .buffer_unordered(jobs)
+        .collect()
+        .await;
+
+    if let Some(total_progress_bar) = progress.total_progress_bar.as_ref() {
+        total_progress_bar.finish();
+    }
+    if let Some(handle) = total_progress_bar_tick_handle {
+        handle.await?;
+    }
+
+    // Writing files changes the modification times of their folders, so this must happen last.
+    // The downloads are done by now, so failures here are only reported.
+    if !options.no_preserve_times && !options.dry_run {
+        for (path, mod_time) in folder_mod_times {
+            if let Err(error) = set_mod_time(path, mod_time.into()).await {
+                eprintln!("{error:?}");
+            }
+        }
+
+        // With "--no-append-name", the output folder belongs to the user, so leave it alone.
+        if !options.no_append_name
+            && let Err(error) = set_root_mod_time(client, options, code, mod_time, out_dir).await
+        {
+            eprintln!("{error:?}");
+        }
+    }
+
+    let succeeded = outcomes
+        .iter()
+        .filter(|(outcome, _)| *outcome == Some(Outcome::Downloaded))
+        .count();
+    let succeeded_size: u64 = outcomes
+        .iter()
+        .filter(|(outcome, _)| *outcome == Some(Outcome::Downloaded))
+        .map(|(_, size)| size)
+        .sum();
+    let skipped = outcomes
+        .iter()
+        .filter(|(outcome, _)| *outcome == Some(Outcome::Skipped))
+        .count();
+    failed += outcomes
+        .iter()
+        .filter(|(outcome, _)| outcome.is_none())
+        .count();
+    if options.dry_run {
+        println!("Would download: {succeeded} ({})", ByteSize(succeeded_size));
+        println!("Would skip: {skipped}");
+    } else {
+        println!("Succeeded: {succeeded} ({})", ByteSize(succeeded_size));
+        println!("Skipped: {skipped}");
+    }
+    println!("Failed: {failed}");
+
+    if failed > 0 {
+        bail!("failed to download {failed} entries");
+    }
 
     Ok(())
 }
 
-pub async fn exec(client: gofile::Client, options: Options) -> anyhow::Result<()> {
-    let config = Config::load().context("failed to load config")?;
+/// Read a list of links from a file, or stdin if the path is "-".
+async fn read_url_list(path: &Path) -> anyhow::Result<Vec<String>> {
+    let text = if path == Path::new("-") {
+        tokio::task::spawn_blocking(|| std::io::read_to_string(std::io::stdin()))
+            .await?
+            .context("failed to read links from stdin")?
+    } else {
+        tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("failed to read links from \"{}\"", path.display()))?
+    };
 
-    let url = Url::parse(&options.url)?;
-    let id = parse_page_url(&url)?;
+    Ok(parse_url_list(&text))
+}
 
-    match config.as_ref().and_then(|config| config.token.as_ref()) {
-        Some(token) => client.set_token(token.clone()),
-        None => client.login_guest().await?,
+/// Download a single file into the output path.
+async fn download_file(
+    client: &gofile::Client,
+    options: &Options,
+    child: &gofile::PageChild,
+) -> anyhow::Result<()> {
+    let mut out_path = options.output.clone();
+    if !options.no_append_name {
+        out_path = out_path.join(options.local_name(&child.name));
+    }
+
+    let filter = Filter::new(&options.filter)?;
+    if !filter.matches_file(&child.name, child) {
+        eprintln!("\"{}\" does not match the filters, skipping...", child.name);
+        return Ok(());
+    }
+
+    if let Some(parent) = out_path.parent()
+        && !options.dry_run
+    {
+        tokio::fs::create_dir_all(&parent).await?;
     }
 
-    let page = client.get_page(id).await.context("failed to get page")?;
+    let progress = Progress::new(None);
+    download_page_child(client, options, &progress, child, out_path).await?;
+
+    Ok(())
+}
+
+/// Download everything from a single link.
+async fn download_url(client: &gofile::Client, options: &Options, url: &str) -> anyhow::Result<()> {
+    let content_ref =
+        gofile::ContentRef::parse(url).with_context(|| format!("invalid link \"{url}\""))?;
+    let id = match content_ref {
+        gofile::ContentRef::Folder { code } => code,
+        gofile::ContentRef::File { .. } => {
+            ensure!(
+                options.child_id.is_none(),
+                "\"--child-id\" can not be used with a file download link"
+            );
+            let child = gofile::PageChild::from_download_link(url)
+                .with_context(|| format!("invalid link \"{url}\""))?;
+            return download_file(client, options, &child).await;
+        }
+    };
 
     match options.child_id.as_ref() {
         Some(child_id) => {
+            let query = gofile::PageQuery {
+                password: options.password.clone(),
+                ..Default::default()
+            };
+            let page = client
+                .get_page_with(&id, &query)
+                .await
+                .context("failed to get page")?;
             let child = page
                 .children
                 .get(child_id)
                 .with_context(|| format!("failed to locate child entry with id \"{child_id}\""))?;
 
-            let mut out_path = options.output.clone();
-            if !options.no_append_name {
-                out_path = out_path.join(child.name.clone());
-            }
+            if child.kind == gofile::PageChildKind::Folder {
+                let mut out_path = options.output.clone();
+                if !options.no_append_name {
+                    out_path = out_path.join(options.local_name(&child.name));
+                }
 
-            if let Some(parent) = out_path.parent() {
-                tokio::fs::create_dir_all(&parent).await?;
+                let code = child.code.as_ref().context("missing folder code")?;
+                let mod_time = child.mod_time.into();
+                return download_folder(client, options, code, Some(mod_time), out_path).await;
             }
 
-            download_page_child(&client, child, out_path).await?;
+            download_file(client, options, child).await?;
         }
         None => {
             let mut out_dir = options.output.clone();
             if !options.no_append_name {
-                out_dir = out_dir.join(&page.code);
+                out_dir = out_dir.join(options.local_name(&id));
             }
-            tokio::fs::create_dir_all(&out_dir).await?;
 
-            for child in page.children.values() {
-                let out_path = out_dir.join(child.name.clone());
-                download_page_child(&client, child, out_path).await?;
-            }
+            download_folder(client, options, &id, None, out_dir).await?;
         }
     }
 
     Ok(())
 }
+
+pub async fn exec(client: gofile::Client, options: Options) -> anyhow::Result<()> {
+    let config = Config::load().context("failed to load config")?;
+
+    // Child ids used to be given as a second argument, which would now be read as a folder id.
+    if let [url, child_id] = options.urls.as_slice()
+        && options.child_id.is_none()
+        && is_id(child_id)
+    {
+        bail!(
+            "\"{child_id}\" looks like the id of a file or folder, not a link. To download one entry of a folder, use \"gofile get {url} --child-id {child_id}\". To download a folder by its id, use its link \"https://gofile.io/d/{child_id}\""
+        );
+    }
+
+    let mut urls = options.urls.clone();
+    if let Some(input_file) = options.input_file.as_ref() {
+        urls.extend(read_url_list(input_file).await?);
+    }
+    ensure!(!urls.is_empty(), "no links to download");
+    ensure!(
+        options.child_id.is_none() || urls.len() == 1,
+        "\"--child-id\" can only be used with a single link"
+    );
+
+    match config.as_ref().and_then(|config| config.token.as_ref()) {
+        Some(token) => client.set_token(token.clone()),
+        None => client.login_guest().await?,
+    }
+
+    if urls.len() == 1 {
+        return download_url(&client, &options, &urls[0]).await;
+    }
+
+    // The client is shared, so every link reuses the same token and website token.
+    let mut failed = 0;
+    let mut results = Vec::with_capacity(urls.len());
+    for url in urls.iter() {
+        let result = download_url(&client, &options, url).await;
+        if let Err(error) = result.as_ref() {
+            eprintln!("failed to download \"{url}\": {error:?}");
+            failed += 1;
+        }
+        results.push((url, result));
+    }
+
+    println!();
+    for (url, result) in results.iter() {
+        match result {
+            Ok(()) => println!("ok: {url}"),
+            Err(error) => println!("failed: {url}: {error}"),
+        }
+    }
+
+    if failed > 0 {
+        bail!("failed to download {failed} of {} links", urls.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::Parser;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    /// Start a server that answers every request with the given body.
+    async fn file_server(body: &'static str) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind test server");
+        let addr = listener.local_addr().expect("missing local addr");
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    let mut byte = [0];
+                    if stream.read(&mut byte).await.unwrap_or(0) == 0 {
+                        break;
+                    }
+                    request.push(byte[0]);
+                }
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        addr
+    }
+
+    /// Make a child like the ones made from download links, with no size or md5 hash.
+    fn link_only_child(link: &str) -> gofile::PageChild {
+        let json = format!(
+            r#"{{
+                "createTime": 1700000000,
+                "modTime": 1700000000,
+                "link": "{link}",
+                "name": "file.txt",
+                "type": "file",
+                "id": "child-id"
+            }}"#
+        );
+        serde_json::from_str(&json).expect("invalid child")
+    }
+
+    #[tokio::test]
+    async fn download_link_only_child() {
+        let addr = file_server("hello").await;
+        let out_dir = std::env::temp_dir().join(format!("gofile-get-link-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&out_dir);
+
+        let client = gofile::Client::builder()
+            .retry_policy(gofile::RetryPolicy::none())
+            .build()
+            .expect("failed to build client");
+        client.set_token("token".into());
+        let child = link_only_child(&format!("http://{addr}/download/web/child-id/file.txt"));
+
+        let options = Options::try_parse_from(["get", "link", "-o", out_dir.to_str().unwrap()])
+            .expect("invalid args");
+        download_file(&client, &options, &child)
+            .await
+            .expect("failed to download");
+        let out_path = out_dir.join("file.txt");
+        assert_eq!(std::fs::read_to_string(&out_path).unwrap(), "hello");
+
+        // There is nothing to verify the existing file against.
+        let options = Options::try_parse_from([
+            "get",
+            "link",
+            "-o",
+            out_dir.to_str().unwrap(),
+            "--on-existing",
+            "verify",
+        ])
+        .expect("invalid args");
+        let progress = Progress::new(None);
+        let outcome = download_page_child(&client, &options, &progress, &child, out_path)
+            .await
+            .expect("failed to check existing file");
+        assert_eq!(outcome, Outcome::Skipped);
+
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
@@ -1,7 +1,6 @@
 use crate::Config;
-use crate::util::parse_page_url;
+use crate::util::parse_folder_code;
 use anyhow::Context;
-use url::Url;
 
 #[derive(Debug, Default, Copy, Clone, clap::ValueEnum)]
 pub enum OutputFormat {
@@ -11,9 +10,48 @@ pub enum OutputFormat {
     Json,
 }
 
+#[derive(Debug, Default, Copy, Clone, clap::ValueEnum)]
+pub enum SortField {
+    #[default]
+    Name,
+    Size,
+    CreateTime,
+    ModTime,
+    DownloadCount,
+}
+
+impl From<SortField> for gofile::SortField {
+    fn from(sort_field: SortField) -> Self {
+        match sort_field {
+            SortField::Name => Self::Name,
+            SortField::Size => Self::Size,
+            SortField::CreateTime => Self::CreateTime,
+            SortField::ModTime => Self::ModTime,
+            SortField::DownloadCount => Self::DownloadCount,
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, clap::ValueEnum)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl From<SortDirection> for gofile::SortDirection {
+    fn from(sort_direction: SortDirection) -> Self {
+        match sort_direction {
+            SortDirection::Asc => Self::Ascending,
+            SortDirection::Desc => Self::Descending,
+        }
+    }
+}
+
 #[derive(Debug, clap::Parser)]
 #[command(about = "Get the info from a https://gofile.io link")]
 pub struct Options {
+    #[arg(help = "A https://gofile.io link or a bare folder code")]
     pub url: String,
 
     #[arg(
@@ -22,6 +60,39 @@ pub struct Options {
         value_enum,
     )]
     pub output_format: OutputFormat,
+
+    #[arg(
+        long = "filter",
+        help = "Only list children whose names match this text"
+    )]
+    pub filter: Option<String>,
+
+    #[arg(
+        long = "sort-field",
+        help = "The field to sort children by",
+        default_value_t = Default::default(),
+        value_enum,
+    )]
+    pub sort_field: SortField,
+
+    #[arg(
+        long = "sort-direction",
+        help = "The direction to sort children in",
+        default_value_t = Default::default(),
+        value_enum,
+    )]
+    pub sort_direction: SortDirection,
+
+    #[arg(
+        long = "page-size",
+        help = "The number of children to request at once",
+        default_value_t = 1000,
+        value_parser = clap::value_parser!(u64).range(1..),
+    )]
+    pub page_size: u64,
+
+    #[arg(long = "password", help = "The password of the folder")]
+    pub password: Option<String>,
 }
 
 fn output_human(page: &gofile::Page) {
@@ -68,15 +139,24 @@ fn output_json(page: &gofile::Page) -> anyhow::Result<()> {
 pub async fn exec(client: gofile::Client, options: Options) -> anyhow::Result<()> {
     let config = Config::load().context("failed to load config")?;
 
-    let url = Url::parse(&options.url)?;
-    let id = parse_page_url(&url)?;
+    let id = parse_folder_code(&options.url)?;
 
     match config.as_ref().and_then(|config| config.token.as_ref()) {
         Some(token) => client.set_token(token.clone()),
         None => client.login_guest().await?,
     }
 
-    let page = client.get_page(id).await.context("failed to get page")?;
+    let query = gofile::PageQuery {
+        content_filter: options.filter.unwrap_or_default(),
+        sort_field: options.sort_field.into(),
+        sort_direction: options.sort_direction.into(),
+        page_size: options.page_size,
+        password: options.password,
+    };
+    let page = client
+        .get_page_with(&id, &query)
+        .await
+        .context("failed to get page")?;
     match options.output_format {
         OutputFormat::Human => output_human(&page),
         OutputFormat::Json => output_json(&page)?,
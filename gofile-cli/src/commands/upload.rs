@@ -1,76 +1,341 @@
 use crate::Config;
+use crate::progress::Progress;
+use crate::progress::spawn_progress_bar_ticker;
+use crate::util::is_id;
+use crate::util::parse_folder_code;
 use anyhow::Context as _;
 use anyhow::bail;
+use anyhow::ensure;
+use bytesize::ByteSize;
+use futures_util::StreamExt;
+use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::task::Context;
 use std::task::Poll;
-use std::time::Duration;
 use tokio::io::AsyncRead;
 use tokio::io::ReadBuf;
 
 #[derive(Debug, clap::Parser)]
-#[command(about = "Upload a file to https://gofile.io")]
+#[command(about = "Upload files and folders to https://gofile.io")]
 pub struct Options {
-    #[arg(help = "The path to the file to upload")]
-    pub path: PathBuf,
+    #[arg(
+        help = "The paths of the files and folders to upload. Folders are uploaded with their subfolders. Use \"-\" to upload stdin",
+        required = true
+    )]
+    pub paths: Vec<PathBuf>,
+
+    #[arg(
+        long = "name",
+        help = "The file name to upload stdin as. Required when uploading stdin"
+    )]
+    pub name: Option<String>,
 
     #[arg(long = "use-guest", help = "Force the use of a guest token")]
     pub use_guest: bool,
+
+    #[arg(
+        long = "folder",
+        help = "The folder to upload into, as an id, code, or link. It must belong to your account"
+    )]
+    pub folder: Option<String>,
+
+    #[arg(
+        long = "jobs",
+        short = 'j',
+        help = "The number of files to upload at the same time",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    pub jobs: u32,
+
+    #[arg(
+        long = "dry-run",
+        help = "List what would be uploaded, without uploading anything"
+    )]
+    pub dry_run: bool,
+}
+
+/// A file to upload, and the remote folder to upload it into.
+#[derive(Debug)]
+struct UploadEntry {
+    path: PathBuf,
+    len: u64,
+
+    /// The names of the remote folders leading to the folder to upload into.
+    ///
+    /// This is empty for the top folder.
+    remote_dir: Vec<String>,
+}
+
+/// Everything to upload.
+#[derive(Debug, Default)]
+struct UploadPlan {
+    /// The remote folders to create, with parents before their children.
+    folders: Vec<Vec<String>>,
+    files: Vec<UploadEntry>,
+}
+
+impl UploadPlan {
+    /// Plan the uploads of the given paths.
+    ///
+    /// If there is only one path and it is a folder, its contents are uploaded into the top folder.
+    /// Otherwise, folders are uploaded as subfolders of the top folder.
+    fn new(paths: &[PathBuf]) -> anyhow::Result<Self> {
+        let mut plan = Self::default();
+        for path in paths {
+            let metadata = std::fs::metadata(path)
+                .with_context(|| format!("failed to get metadata for \"{}\"", path.display()))?;
+            if !metadata.is_dir() {
+                plan.files.push(UploadEntry {
+                    path: path.clone(),
+                    len: metadata.len(),
+                    remote_dir: Vec::new(),
+                });
+                continue;
+            }
+
+            let mut remote_dir = Vec::new();
+            if paths.len() > 1 {
+                let path = path.canonicalize()?;
+                let name = path
+                    .file_name()
+                    .with_context(|| format!("\"{}\" has no name", path.display()))?;
+                remote_dir.push(name.to_string_lossy().into_owned());
+                ensure!(
+                    !plan.folders.contains(&remote_dir),
+                    "more than one folder is named \"{}\". Upload them separately or rename one",
+                    remote_dir[0]
+                );
+                plan.folders.push(remote_dir.clone());
+            }
+            plan.add_dir(path, remote_dir, &mut Vec::new())?;
+        }
+
+        // The first upload creates the top folder, so it needs to be a file at the top.
+        plan.files.sort_by_key(|entry| !entry.remote_dir.is_empty());
+
+        Ok(plan)
+    }
+
+    /// Add the contents of a folder.
+    ///
+    /// `ancestors` holds the canonical paths of the folders being added,
+    /// which catches symlinks that loop back to one of them.
+    fn add_dir(
+        &mut self,
+        path: &Path,
+        remote_dir: Vec<String>,
+        ancestors: &mut Vec<PathBuf>,
+    ) -> anyhow::Result<()> {
+        let canonical_path = path
+            .canonicalize()
+            .with_context(|| format!("failed to resolve \"{}\"", path.display()))?;
+        ensure!(
+            !ancestors.contains(&canonical_path),
+            "\"{}\" links back to a folder containing it",
+            path.display()
+        );
+        ancestors.push(canonical_path);
+
+        let mut entries = std::fs::read_dir(path)
+            .with_context(|| format!("failed to read folder \"{}\"", path.display()))?
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            let metadata = std::fs::metadata(&path)
+                .with_context(|| format!("failed to get metadata for \"{}\"", path.display()))?;
+            if metadata.is_dir() {
+                let mut remote_dir = remote_dir.clone();
+                remote_dir.push(entry.file_name().to_string_lossy().into_owned());
+                self.folders.push(remote_dir.clone());
+                self.add_dir(&path, remote_dir, ancestors)?;
+            } else {
+                self.files.push(UploadEntry {
+                    path,
+                    len: metadata.len(),
+                    remote_dir: remote_dir.clone(),
+                });
+            }
+        }
+
+        ancestors.pop();
+
+        Ok(())
+    }
+}
+
+/// Get the id and code of a folder from its id, code, or link.
+///
+/// The code is not known if given an id.
+async fn resolve_folder(
+    client: &gofile::Client,
+    folder: &str,
+) -> anyhow::Result<(String, Option<String>)> {
+    let code = parse_folder_code(folder)?;
+    if is_id(&code) {
+        return Ok((code, None));
+    }
+
+    // Only the folder's id is needed, so skip listing all of its children.
+    let query = gofile::PageQuery {
+        page_size: 1,
+        ..Default::default()
+    };
+    let page = client
+        .get_page_number(&code, &query, 1)
+        .await
+        .with_context(|| format!("failed to get folder \"{folder}\""))?;
+    Ok((page.id, Some(page.code)))
+}
+
+/// Upload a single file, showing its progress.
+async fn upload_file(
+    client: &gofile::Client,
+    progress: &Progress,
+    path: &Path,
+    folder_id: Option<String>,
+) -> anyhow::Result<gofile::UploadInfo> {
+    let (file, progress_bar) = UploadProgressTracker::new_multipart_part(progress, path).await?;
+    let progress_bar_tick_handle = spawn_progress_bar_ticker(progress_bar.clone());
+    let result = client
+        .upload_with(file, &gofile::UploadOptions { folder_id })
+        .await
+        .with_context(|| format!("failed to upload \"{}\"", path.display()));
+    progress_bar.finish();
+    progress_bar_tick_handle.await?;
+    if progress.total_progress_bar.is_some() {
+        progress.multi_progress.remove(&progress_bar);
+    }
+
+    result
+}
+
+/// Upload everything read from stdin as a single file, showing its progress.
+async fn upload_stdin(
+    client: &gofile::Client,
+    name: &str,
+    folder_id: Option<String>,
+) -> anyhow::Result<gofile::UploadInfo> {
+    let progress = Progress::new(None);
+    let (file, progress_bar) = UploadProgressTracker::new_reader_part(
+        &progress,
+        tokio::io::stdin(),
+        None,
+        Some(name.to_string()),
+        &guess_mime(Path::new(name)),
+    )?;
+    let progress_bar_tick_handle = spawn_progress_bar_ticker(progress_bar.clone());
+    let result = client
+        .upload_with(file, &gofile::UploadOptions { folder_id })
+        .await
+        .context("failed to upload stdin");
+    progress_bar.finish();
+    progress_bar_tick_handle.await?;
+
+    result
+}
+
+/// Print a table of uploaded files, with columns lined up.
+fn print_upload_table(uploads: &[(UploadEntry, gofile::UploadInfo)]) {
+    let rows: Vec<[String; 3]> = uploads
+        .iter()
+        .map(|(entry, upload_info)| {
+            [
+                entry.path.display().to_string(),
+                ByteSize(upload_info.size).to_string(),
+                upload_info.download_page.clone(),
+            ]
+        })
+        .collect();
+    let header = ["Path", "Size", "Url"].map(String::from);
+    let mut widths = [0; 3];
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for [path, size, url] in std::iter::once(&header).chain(rows.iter()) {
+        println!(
+            "{path:<path_width$}  {size:>size_width$}  {url}",
+            path_width = widths[0],
+            size_width = widths[1],
+        );
+    }
+}
+
+fn print_upload_info(upload_info: &gofile::UploadInfo) {
+    println!("Url: {}", upload_info.download_page);
+    println!("Id: {}", upload_info.id);
+    println!("Size: {}", upload_info.size);
+    println!("Parent Folder Id: {}", upload_info.parent_folder);
+    println!("Parent Folder Code: {}", upload_info.parent_folder_code);
+    if let Some(guest_token) = upload_info.guest_token.as_ref() {
+        println!("Guest Token: {guest_token}");
+    }
+}
+
+/// Guess the mime type of a file from its extension.
+fn guess_mime(path: &Path) -> mime_guess::Mime {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    mime_guess::from_ext(extension).first_or_octet_stream()
 }
 
 pin_project_lite::pin_project! {
-    struct UploadProgressTracker {
+    struct UploadProgressTracker<R> {
         progress_bar: indicatif::ProgressBar,
+        total_progress_bar: Option<indicatif::ProgressBar>,
         position: u64,
-        len: u64,
         #[pin]
-        file: tokio::fs::File,
+        reader: R,
     }
 }
 
-impl UploadProgressTracker {
+impl UploadProgressTracker<tokio::fs::File> {
     pub async fn new_multipart_part(
+        progress: &Progress,
         path: &Path,
     ) -> anyhow::Result<(gofile::MultipartPart, indicatif::ProgressBar)> {
         let file_name = path
             .file_name()
             .map(|filename| filename.to_string_lossy().into_owned());
-        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-        let mime = mime_guess::from_ext(extension).first_or_octet_stream();
+        let mime = guess_mime(path);
 
         let file = tokio::fs::File::open(path).await?;
         let metadata = file.metadata().await?;
         let len = metadata.len();
 
-        let progress_bar = indicatif::ProgressBar::new(len);
-        let progress_bar_style_template = "[Time = {elapsed_precise} | ETA = {eta_precise} | Speed = {bytes_per_sec}] {wide_bar} {bytes}/{total_bytes}";
-        let progress_bar_style = indicatif::ProgressStyle::default_bar()
-            .template(progress_bar_style_template)
-            .expect("invalid progress bar style template");
-        progress_bar.set_style(progress_bar_style);
+        UploadProgressTracker::new_reader_part(progress, file, Some(len), file_name, &mime)
+    }
+}
 
-        {
-            let progress_bar = progress_bar.clone();
-            tokio::spawn(async move {
-                while !progress_bar.is_finished() {
-                    progress_bar.tick();
-                    tokio::time::sleep(Duration::from_millis(1_000)).await;
-                }
-            });
-        }
+impl<R> UploadProgressTracker<R>
+where
+    R: AsyncRead + Send + 'static,
+{
+    /// Make a part that uploads everything read from a reader, which may not have a known length.
+    pub fn new_reader_part(
+        progress: &Progress,
+        reader: R,
+        len: Option<u64>,
+        file_name: Option<String>,
+        mime: &mime_guess::Mime,
+    ) -> anyhow::Result<(gofile::MultipartPart, indicatif::ProgressBar)> {
+        let progress_bar =
+            progress.add_file_progress_bar(len, file_name.as_deref().unwrap_or_default());
 
         let tracker = Self {
             progress_bar: progress_bar.clone(),
+            total_progress_bar: progress.total_progress_bar.clone(),
             position: 0,
-            len,
-            file,
+            reader,
         };
 
-        let stream = reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(tracker));
-        let mut part =
-            gofile::MultipartPart::stream_with_length(stream, len).mime_str(mime.essence_str())?;
+        let mut part = gofile::reader_part(tracker, len).mime_str(mime.essence_str())?;
         if let Some(file_name) = file_name {
             part = part.file_name(file_name);
         }
@@ -79,7 +344,10 @@ impl UploadProgressTracker {
     }
 }
 
-impl AsyncRead for UploadProgressTracker {
+impl<R> AsyncRead for UploadProgressTracker<R>
+where
+    R: AsyncRead,
+{
     fn poll_read(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
@@ -88,11 +356,14 @@ impl AsyncRead for UploadProgressTracker {
         let this = self.as_mut().project();
 
         let start = buf.filled().len();
-        let result = this.file.poll_read(cx, buf);
+        let result = this.reader.poll_read(cx, buf);
         let end = buf.filled().len();
         let change = u64::try_from(end - start).unwrap();
         *this.position += change;
         self.progress_bar.inc(change);
+        if let Some(total_progress_bar) = self.total_progress_bar.as_ref() {
+            total_progress_bar.inc(change);
+        }
 
         result
     }
@@ -101,14 +372,59 @@ impl AsyncRead for UploadProgressTracker {
 pub async fn exec(client: gofile::Client, options: Options) -> anyhow::Result<()> {
     let config = Config::load().context("failed to load config")?;
 
-    if !options
-        .path
-        .try_exists()
-        .context("failed to check if file exists")?
-    {
-        bail!("file \"{}\" does not exist", options.path.display());
+    // Stdin is uploaded on its own, as a single file.
+    let stdin_name = if options.paths.iter().any(|path| path == Path::new("-")) {
+        ensure!(
+            options.paths.len() == 1,
+            "stdin can not be uploaded along with other paths"
+        );
+        let name = options
+            .name
+            .as_deref()
+            .context("uploading stdin needs a file name. Use \"--name\" to set one")?;
+        Some(name)
+    } else {
+        ensure!(
+            options.name.is_none(),
+            "\"--name\" can only be used when uploading stdin"
+        );
+        None
+    };
+
+    let plan = match stdin_name {
+        Some(_name) => UploadPlan::default(),
+        None => {
+            let plan = UploadPlan::new(&options.paths)?;
+            ensure!(!plan.files.is_empty(), "no files to upload");
+            plan
+        }
+    };
+
+    if options.dry_run {
+        if let Some(name) = stdin_name {
+            println!(
+                "Would upload stdin as \"{name}\" ({})",
+                guess_mime(Path::new(name))
+            );
+        }
+        for entry in plan.files.iter() {
+            println!(
+                "Would upload \"{}\" to \"/{}\" ({}, {})",
+                entry.path.display(),
+                entry.remote_dir.join("/"),
+                ByteSize(entry.len),
+                guess_mime(&entry.path),
+            );
+        }
+        match options.folder.as_ref() {
+            Some(folder) => {
+                parse_folder_code(folder)?;
+                println!("Folder: {folder}");
+            }
+            None => println!("Folder: a new folder"),
+        }
+        return Ok(());
     }
-    let (file, progress_bar) = UploadProgressTracker::new_multipart_part(&options.path).await?;
 
     if options.use_guest {
         client
@@ -123,17 +439,191 @@ pub async fn exec(client: gofile::Client, options: Options) -> anyhow::Result<()
             .context(missing_token_message)?;
         client.set_token(token.clone());
     }
-    let upload_info = client.upload(file).await.context("failed to upload file")?;
-    progress_bar.finish();
 
-    println!("Url: {}", upload_info.download_page);
-    println!("Id: {}", upload_info.id);
-    println!("Size: {}", upload_info.size);
-    println!("Parent Folder Id: {}", upload_info.parent_folder);
-    println!("Parent Folder Code: {}", upload_info.parent_folder_code);
-    if let Some(guest_token) = upload_info.guest_token {
+    if let Some(name) = stdin_name {
+        let folder_id = match options.folder.as_ref() {
+            Some(folder) => Some(resolve_folder(&client, folder).await?.0),
+            None => None,
+        };
+        let upload_info = upload_stdin(&client, name, folder_id).await?;
+        print_upload_info(&upload_info);
+        return Ok(());
+    }
+
+    let total_size = plan.files.iter().map(|entry| entry.len).sum();
+    let progress = Progress::new((plan.files.len() > 1).then_some(total_size));
+    let total_progress_bar_tick_handle = progress
+        .total_progress_bar
+        .clone()
+        .map(spawn_progress_bar_ticker);
+
+    let mut files = plan.files.into_iter();
+    let mut uploads = Vec::new();
+    let (folder_id, mut folder_code) = match options.folder.as_ref() {
+        Some(folder) => resolve_folder(&client, folder).await?,
+        None => {
+            let entry = files.next().expect("missing file");
+            ensure!(
+                entry.remote_dir.is_empty(),
+                "a new folder can only be made by uploading a file into it, but all files are in subfolders. Use \"--folder\" to upload into an existing folder"
+            );
+            let upload_info = upload_file(&client, &progress, &entry.path, None).await?;
+
+            // Reuse the guest token, since only its account can add to the new folder.
+            if let Some(guest_token) = upload_info.guest_token.as_ref() {
+                client.set_token(guest_token.clone());
+            }
+            let folder = (
+                upload_info.parent_folder.clone(),
+                Some(upload_info.parent_folder_code.clone()),
+            );
+            uploads.push((entry, upload_info));
+            folder
+        }
+    };
+
+    let mut folder_ids = HashMap::from([(Vec::new(), folder_id.clone())]);
+    for remote_dir in plan.folders {
+        let (name, parent) = remote_dir.split_last().expect("empty folder path");
+        let parent_id = folder_ids.get(parent).expect("missing parent folder");
+        let folder = client
+            .create_folder(parent_id, name)
+            .await
+            .with_context(|| format!("failed to create folder \"/{}\"", remote_dir.join("/")))?;
+        folder_ids.insert(remote_dir, folder.id);
+    }
+
+    let jobs = usize::try_from(options.jobs)?;
+    let mut results: Vec<(usize, UploadEntry, Option<gofile::UploadInfo>)> =
+        futures_util::stream::iter(files.enumerate())
+            .map(|(index, entry)| {
+                let client = &client;
+                let progress = &progress;
+                let folder_id = folder_ids
+                    .get(&entry.remote_dir)
+                    .expect("missing folder")
+                    .clone();
+                async move {
+                    match upload_file(client, progress, &entry.path, Some(folder_id)).await {
+                        Ok(upload_info) => (index, entry, Some(upload_info)),
+                        Err(error) => {
+                            progress.println(&format!("{error:?}"));
+                            (index, entry, None)
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(jobs)
+            .collect()
+            .await;
+
+    if let Some(total_progress_bar) = progress.total_progress_bar.as_ref() {
+        total_progress_bar.finish();
+    }
+    if let Some(handle) = total_progress_bar_tick_handle {
+        handle.await?;
+    }
+
+    // Show the results in the order the files were planned, not the order they finished.
+    results.sort_by_key(|(index, _entry, _upload_info)| *index);
+    let mut failed = 0;
+    for (_index, entry, upload_info) in results {
+        match upload_info {
+            Some(upload_info) => uploads.push((entry, upload_info)),
+            None => failed += 1,
+        }
+    }
+
+    if let ([(_entry, upload_info)], 0) = (uploads.as_slice(), failed) {
+        print_upload_info(upload_info);
+        return Ok(());
+    }
+
+    if folder_code.is_none() {
+        folder_code = uploads
+            .iter()
+            .find(|(entry, _upload_info)| entry.remote_dir.is_empty())
+            .map(|(_entry, upload_info)| upload_info.parent_folder_code.clone());
+    }
+
+    print_upload_table(&uploads);
+    println!();
+    match folder_code {
+        Some(folder_code) => println!("Url: https://gofile.io/d/{folder_code}"),
+        None => println!("Folder Id: {folder_id}"),
+    }
+    if let Some(guest_token) = uploads
+        .iter()
+        .find_map(|(_entry, upload_info)| upload_info.guest_token.as_ref())
+    {
         println!("Guest Token: {guest_token}");
     }
+    println!(
+        "Succeeded: {} ({})",
+        uploads.len(),
+        ByteSize(uploads.iter().map(|(entry, _upload_info)| entry.len).sum())
+    );
+    println!("Failed: {failed}");
+
+    if failed > 0 {
+        bail!("failed to upload {failed} files");
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plan_mirrors_folders() {
+        let root = std::env::temp_dir().join(format!("gofile-upload-plan-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("dir/sub")).unwrap();
+        std::fs::create_dir_all(root.join("dir/empty")).unwrap();
+        std::fs::write(root.join("dir/sub/b.txt"), "b").unwrap();
+        std::fs::write(root.join("dir/a.txt"), "a").unwrap();
+        std::fs::write(root.join("top.txt"), "top").unwrap();
+
+        let plan = UploadPlan::new(&[root.join("dir")]).unwrap();
+        assert_eq!(plan.folders, [vec!["empty"], vec!["sub"]]);
+        let files: Vec<_> = plan
+            .files
+            .iter()
+            .map(|entry| (entry.path.clone(), entry.remote_dir.clone()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                (root.join("dir/a.txt"), vec![]),
+                (root.join("dir/sub/b.txt"), vec!["sub".to_string()]),
+            ]
+        );
+
+        let plan = UploadPlan::new(&[root.join("dir"), root.join("top.txt")]).unwrap();
+        assert_eq!(
+            plan.folders,
+            [vec!["dir"], vec!["dir", "empty"], vec!["dir", "sub"]]
+        );
+        assert_eq!(plan.files[0].path, root.join("top.txt"));
+        assert!(plan.files[0].remote_dir.is_empty());
+        assert_eq!(plan.files.len(), 3);
+
+        std::fs::create_dir_all(root.join("other/dir")).unwrap();
+        let error = UploadPlan::new(&[root.join("dir"), root.join("other/dir")]).unwrap_err();
+        assert!(
+            error.to_string().contains("more than one folder"),
+            "{error}"
+        );
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("dir"), root.join("dir/sub/loop")).unwrap();
+            let error = UploadPlan::new(&[root.join("dir")]).unwrap_err();
+            assert!(error.to_string().contains("links back"), "{error}");
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
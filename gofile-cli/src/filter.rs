@@ -0,0 +1,267 @@
+use anyhow::Context;
+use bytesize::ByteSize;
+use globset::Glob;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+use regex::Regex;
+use std::time::SystemTime;
+
+/// Parse a time, either as an age like "7d" or as a date like "2024-01-31" or "2024-01-31T12:00:00Z".
+fn parse_time(value: &str) -> Result<SystemTime, String> {
+    if let Ok(age) = humantime::parse_duration(value) {
+        return SystemTime::now()
+            .checked_sub(age)
+            .ok_or_else(|| format!("age \"{value}\" is too large"));
+    }
+
+    let timestamp = if value.len() == "YYYY-MM-DD".len() {
+        format!("{value}T00:00:00Z")
+    } else {
+        value.to_string()
+    };
+    humantime::parse_rfc3339_weak(&timestamp).map_err(|_error| {
+        format!("\"{value}\" is not an age like \"7d\" or a date like \"2024-01-31\"")
+    })
+}
+
+/// Options for choosing which files to download.
+#[derive(Debug, Clone, clap::Args)]
+pub struct FilterOptions {
+    #[arg(
+        long = "include",
+        help = "Only download files whose path or name matches this glob pattern. May be given more than once"
+    )]
+    pub include: Vec<String>,
+
+    #[arg(
+        long = "exclude",
+        help = "Do not download files or enter folders whose path or name matches this glob pattern. May be given more than once"
+    )]
+    pub exclude: Vec<String>,
+
+    #[arg(
+        long = "min-size",
+        help = "Only download files of at least this size, like \"10MB\""
+    )]
+    pub min_size: Option<ByteSize>,
+
+    #[arg(
+        long = "max-size",
+        help = "Only download files of at most this size, like \"1.5GiB\""
+    )]
+    pub max_size: Option<ByteSize>,
+
+    #[arg(
+        long = "newer-than",
+        help = "Only download files modified after this time, either an age like \"7d\" or a date like \"2024-01-31\"",
+        value_parser = parse_time,
+    )]
+    pub newer_than: Option<SystemTime>,
+
+    #[arg(
+        long = "older-than",
+        help = "Only download files modified before this time, either an age like \"7d\" or a date like \"2024-01-31\"",
+        value_parser = parse_time,
+    )]
+    pub older_than: Option<SystemTime>,
+
+    #[arg(
+        long = "name-regex",
+        help = "Only download files whose name matches this regular expression"
+    )]
+    pub name_regex: Option<Regex>,
+}
+
+/// A compiled set of filters.
+#[derive(Debug)]
+pub struct Filter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    options: FilterOptions,
+}
+
+impl Filter {
+    /// Compile the filters from the given options.
+    pub fn new(options: &FilterOptions) -> anyhow::Result<Self> {
+        let include = if options.include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(&options.include)?)
+        };
+        let exclude = build_glob_set(&options.exclude)?;
+
+        Ok(Self {
+            include,
+            exclude,
+            options: options.clone(),
+        })
+    }
+
+    /// Check if a glob set matches a path, relative to the folder being downloaded, or the name at the end of it.
+    fn glob_set_matches(glob_set: &GlobSet, path: &str) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        glob_set.is_match(path) || glob_set.is_match(name)
+    }
+
+    /// Check if a folder should be entered.
+    ///
+    /// The path should be relative to the folder being downloaded, using "/" as the separator.
+    /// The path is also checked with a trailing "/", so patterns like "skip/**" exclude "skip" itself.
+    pub fn matches_folder(&self, path: &str) -> bool {
+        !Self::glob_set_matches(&self.exclude, path) && !self.exclude.is_match(format!("{path}/"))
+    }
+
+    /// Check if a file should be downloaded.
+    ///
+    /// The path should be relative to the folder being downloaded, using "/" as the separator.
+    pub fn matches_file(&self, path: &str, child: &gofile::PageChild) -> bool {
+        if let Some(include) = self.include.as_ref()
+            && !Self::glob_set_matches(include, path)
+        {
+            return false;
+        }
+        if Self::glob_set_matches(&self.exclude, path) {
+            return false;
+        }
+
+        let size = child.size.unwrap_or(0);
+        if self
+            .options
+            .min_size
+            .is_some_and(|min_size| size < min_size.as_u64())
+        {
+            return false;
+        }
+        if self
+            .options
+            .max_size
+            .is_some_and(|max_size| size > max_size.as_u64())
+        {
+            return false;
+        }
+
+        let mod_time = SystemTime::from(child.mod_time);
+        if self
+            .options
+            .newer_than
+            .is_some_and(|newer_than| mod_time <= newer_than)
+        {
+            return false;
+        }
+        if self
+            .options
+            .older_than
+            .is_some_and(|older_than| mod_time >= older_than)
+        {
+            return false;
+        }
+
+        if let Some(name_regex) = self.options.name_regex.as_ref()
+            && !name_regex.is_match(&child.name)
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob =
+            Glob::new(pattern).with_context(|| format!("invalid glob pattern \"{pattern}\""))?;
+        builder.add(glob);
+    }
+
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Debug, clap::Parser)]
+    struct Options {
+        #[command(flatten)]
+        filter: FilterOptions,
+    }
+
+    fn filter(args: &[&str]) -> Filter {
+        let options = Options::try_parse_from(std::iter::once("test").chain(args.iter().copied()))
+            .expect("invalid args");
+        Filter::new(&options.filter).expect("invalid filter")
+    }
+
+    fn child(name: &str, size: u64, mod_time: i64) -> gofile::PageChild {
+        let json = format!(
+            r#"{{
+                "createTime": 1700000000,
+                "modTime": {mod_time},
+                "link": "https://example.com/{name}",
+                "md5": "00000000000000000000000000000000",
+                "size": {size},
+                "name": "{name}",
+                "type": "file",
+                "id": "child-id"
+            }}"#
+        );
+        serde_json::from_str(&json).expect("invalid child")
+    }
+
+    #[test]
+    fn globs() {
+        let filter = filter(&["--include", "*.txt", "--exclude", "skip/**"]);
+        assert!(filter.matches_file("a.txt", &child("a.txt", 1, 0)));
+        assert!(filter.matches_file("sub/a.txt", &child("a.txt", 1, 0)));
+        assert!(!filter.matches_file("a.bin", &child("a.bin", 1, 0)));
+        assert!(!filter.matches_file("skip/a.txt", &child("a.txt", 1, 0)));
+        assert!(filter.matches_folder("sub"));
+        assert!(!filter.matches_folder("skip/deeper"));
+        assert!(!filter.matches_folder("skip"));
+
+        let filter = self::filter(&["--exclude", "**/cache/**", "--exclude", "x"]);
+        assert!(!filter.matches_folder("a/cache"));
+        assert!(!filter.matches_folder("x"));
+        assert!(filter.matches_folder("skip"));
+    }
+
+    #[test]
+    fn sizes_and_times() {
+        let filter = filter(&[
+            "--min-size",
+            "10B",
+            "--max-size",
+            "1KB",
+            "--newer-than",
+            "2023-01-01",
+            "--older-than",
+            "2024-01-01T00:00:00Z",
+        ]);
+        // 2023-11-14
+        let mod_time = 1_700_000_000;
+        assert!(filter.matches_file("a", &child("a", 10, mod_time)));
+        assert!(!filter.matches_file("a", &child("a", 9, mod_time)));
+        assert!(!filter.matches_file("a", &child("a", 1001, mod_time)));
+        // 2022-07-14
+        assert!(!filter.matches_file("a", &child("a", 10, 1_657_800_000)));
+        // 2024-03-10
+        assert!(!filter.matches_file("a", &child("a", 10, 1_710_000_000)));
+    }
+
+    #[test]
+    fn name_regex() {
+        let filter = filter(&["--name-regex", r"^part\d+\.rar$"]);
+        assert!(filter.matches_file("sub/part1.rar", &child("part1.rar", 1, 0)));
+        assert!(!filter.matches_file("sub/readme.txt", &child("readme.txt", 1, 0)));
+    }
+
+    #[test]
+    fn parse_ages() {
+        let time = parse_time("1h").expect("invalid age");
+        let age = SystemTime::now().duration_since(time).unwrap();
+        assert!(age.as_secs() >= 60 * 60);
+        assert!(parse_time("yesterday").is_err());
+    }
+}
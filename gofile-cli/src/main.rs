@@ -1,5 +1,7 @@
 mod commands;
 mod config;
+mod filter;
+mod progress;
 mod util;
 
 pub use self::config::Config;
@@ -36,7 +38,7 @@ struct Options {
 
 #[derive(Debug, clap::Subcommand)]
 enum Subcommand {
-    Get(self::commands::get::Options),
+    Get(Box<self::commands::get::Options>),
     Config(self::commands::config::Options),
     Upload(self::commands::upload::Options),
     Info(self::commands::info::Options),
@@ -46,7 +48,7 @@ enum Subcommand {
 async fn async_main(options: Options) -> anyhow::Result<()> {
     let client = gofile::Client::new();
     match options.subcommand {
-        Subcommand::Get(options) => self::commands::get::exec(client, options).await?,
+        Subcommand::Get(options) => self::commands::get::exec(client, *options).await?,
         Subcommand::Config(options) => self::commands::config::exec(client, options).await?,
         Subcommand::Upload(options) => self::commands::upload::exec(client, options).await?,
         Subcommand::Info(options) => self::commands::info::exec(client, options).await?,
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+/// The progress bars of a transfer of one or more files.
+pub struct Progress {
+    pub multi_progress: indicatif::MultiProgress,
+
+    /// The progress of all files, if transferring more than one.
+    pub total_progress_bar: Option<indicatif::ProgressBar>,
+}
+
+impl Progress {
+    pub fn new(total_size: Option<u64>) -> Self {
+        let multi_progress = indicatif::MultiProgress::new();
+        let total_progress_bar = total_size.map(|total_size| {
+            let progress_bar = multi_progress.add(indicatif::ProgressBar::new(total_size));
+            let progress_bar_style_template =
+                "[Total | Time = {elapsed_precise} | ETA = {eta_precise}] {wide_bar} {bytes}/{total_bytes}";
+            let progress_bar_style = indicatif::ProgressStyle::default_bar()
+                .template(progress_bar_style_template)
+                .expect("invalid progress bar style template");
+            progress_bar.set_style(progress_bar_style);
+            progress_bar
+        });
+
+        Self {
+            multi_progress,
+            total_progress_bar,
+        }
+    }
+
+    /// Add a progress bar for a single file.
+    ///
+    /// If the size of the file is not known, the bar only counts bytes.
+    pub fn add_file_progress_bar(&self, len: Option<u64>, name: &str) -> indicatif::ProgressBar {
+        let (progress_bar, progress_bar_style_template) = match len {
+            Some(len) => (
+                indicatif::ProgressBar::new(len),
+                "[Time = {elapsed_precise} | ETA = {eta_precise} | Speed = {bytes_per_sec}] {wide_bar} {bytes}/{total_bytes} {msg}",
+            ),
+            None => (
+                indicatif::ProgressBar::no_length(),
+                "[Time = {elapsed_precise} | Speed = {bytes_per_sec}] {spinner} {bytes} {msg}",
+            ),
+        };
+        let progress_bar = self.multi_progress.add(progress_bar);
+        let progress_bar_style = indicatif::ProgressStyle::default_bar()
+            .template(progress_bar_style_template)
+            .expect("invalid progress bar style template");
+        progress_bar.set_style(progress_bar_style);
+        progress_bar.set_message(name.to_string());
+        progress_bar
+    }
+
+    /// Print a line without breaking the progress bars.
+    pub fn println(&self, line: &str) {
+        self.multi_progress.suspend(|| eprintln!("{line}"));
+    }
+}
+
+pub fn spawn_progress_bar_ticker(
+    progress_bar: indicatif::ProgressBar,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while !progress_bar.is_finished() {
+            progress_bar.tick();
+            tokio::time::sleep(Duration::from_millis(1_000)).await;
+        }
+    })
+}
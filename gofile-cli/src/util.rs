@@ -1,14 +1,198 @@
 use anyhow::Context;
-use anyhow::ensure;
-use url::Url;
+use anyhow::bail;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
 
-pub fn parse_page_url(url: &Url) -> anyhow::Result<&str> {
-    ensure!(url.host_str() == Some("gofile.io"));
+/// Parse a link or bare code into the code of a folder.
+pub fn parse_folder_code(input: &str) -> anyhow::Result<String> {
+    let content_ref =
+        gofile::ContentRef::parse(input).with_context(|| format!("invalid link \"{input}\""))?;
+    match content_ref {
+        gofile::ContentRef::Folder { code } => Ok(code),
+        gofile::ContentRef::File { id } => bail!(
+            "\"{input}\" is a download link for the file \"{id}\", which does not say which folder it is in. Use the link of its folder instead"
+        ),
+    }
+}
+
+/// Check if a code is actually the id of a file or folder.
+///
+/// Ids are uuids, while folder codes are short.
+pub fn is_id(code: &str) -> bool {
+    code.len() == 36 && code.chars().filter(|c| *c == '-').count() == 4
+}
+
+/// Parse a list of links, one per line.
+///
+/// Blank lines and lines starting with "#" are ignored.
+pub fn parse_url_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// The maximum length of a file name on most file systems, in bytes.
+const MAX_FILE_NAME_LEN: usize = 255;
+
+/// Names that cannot be used for files on Windows, with or without an extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Split a file name into its stem and extension, where the extension includes the dot.
+///
+/// Overlong extensions are treated as part of the stem.
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(index) if index > 0 && name.len() - index < MAX_FILE_NAME_LEN / 2 => {
+            name.split_at(index)
+        }
+        _ => (name, ""),
+    }
+}
+
+/// Join a stem and a suffix, shortening the stem so that the result is not overlong.
+fn fit_file_name(stem: &str, suffix: &str) -> String {
+    let mut len = MAX_FILE_NAME_LEN
+        .saturating_sub(suffix.len())
+        .min(stem.len());
+    while !stem.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    format!("{}{suffix}", &stem[..len])
+}
+
+/// Make a remote file name safe to use as a single component of a local path.
+///
+/// This replaces path separators, control characters, and characters Windows does not allow,
+/// and makes sure the result is not empty, "." or "..", a reserved name, or overlong.
+pub fn sanitize_file_name(name: &str) -> String {
+    let mut name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    // Windows strips trailing dots and spaces, which also turns ".." into "".
+    let len = name.trim_end_matches(['.', ' ']).len();
+    name.truncate(len);
+    if name.is_empty() {
+        name.push('_');
+    }
+
+    let base_name = name.split('.').next().unwrap_or_default().trim_end();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(base_name))
+    {
+        name.insert(0, '_');
+    }
+
+    let (stem, extension) = split_extension(&name);
+    fit_file_name(stem, extension)
+}
+
+/// Add a number to a file name, before its extension, like "file (1).txt".
+pub fn number_file_name(name: &str, n: usize) -> String {
+    let (stem, extension) = split_extension(name);
+    fit_file_name(stem, &format!(" ({n}){extension}"))
+}
+
+/// Picks unique names for the entries of local folders.
+///
+/// Names are compared case-insensitively,
+/// since many file systems do not tell names that only differ by case apart.
+#[derive(Debug, Default)]
+pub struct UniqueNames {
+    taken: HashMap<PathBuf, HashSet<String>>,
+}
+
+impl UniqueNames {
+    /// Get an unused name in the given folder, based on the given name.
+    ///
+    /// If the name is taken, a number is added to it, like "file (1).txt".
+    pub fn claim(&mut self, dir: &Path, name: &str) -> String {
+        let taken = self.taken.entry(dir.to_path_buf()).or_default();
+
+        let mut candidate = name.to_string();
+        let mut n = 1;
+        while !taken.insert(candidate.to_lowercase()) {
+            candidate = number_file_name(name, n);
+            n += 1;
+        }
+
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn url_list() {
+        let text = "# links\nhttps://gofile.io/d/a\n\n  https://gofile.io/d/b  \r\n  # done\n";
+        assert_eq!(
+            parse_url_list(text),
+            ["https://gofile.io/d/a", "https://gofile.io/d/b"]
+        );
+    }
+
+    #[test]
+    fn sanitize_file_name_blocks_traversal() {
+        assert_eq!(sanitize_file_name("../../.bashrc"), ".._.._.bashrc");
+        assert_eq!(sanitize_file_name(".."), "_");
+        assert_eq!(sanitize_file_name("."), "_");
+        assert_eq!(sanitize_file_name(""), "_");
+        assert_eq!(sanitize_file_name("a/b\\c"), "a_b_c");
+        assert_eq!(sanitize_file_name("/etc/passwd"), "_etc_passwd");
+    }
+
+    #[test]
+    fn sanitize_file_name_windows() {
+        assert_eq!(sanitize_file_name("a\0b\nc"), "a_b_c");
+        assert_eq!(sanitize_file_name("what?<>:\"|*"), "what_______");
+        assert_eq!(sanitize_file_name("name. . "), "name");
+        assert_eq!(sanitize_file_name("con"), "_con");
+        assert_eq!(sanitize_file_name("NUL.txt"), "_NUL.txt");
+        assert_eq!(sanitize_file_name("lpt1.tar.gz"), "_lpt1.tar.gz");
+        assert_eq!(sanitize_file_name("console.txt"), "console.txt");
+    }
+
+    #[test]
+    fn sanitize_file_name_overlong() {
+        let name = format!("{}.txt", "a".repeat(300));
+        let sanitized = sanitize_file_name(&name);
+        assert_eq!(sanitized.len(), MAX_FILE_NAME_LEN);
+        assert!(sanitized.ends_with("a.txt"));
 
-    let mut path_iter = url.path_segments().context("missing path")?;
-    ensure!(path_iter.next() == Some("d"));
-    let id = path_iter.next().context("missing id")?;
-    ensure!(path_iter.next().is_none());
+        // Multibyte characters should not be split.
+        let name = "é".repeat(200);
+        let sanitized = sanitize_file_name(&name);
+        assert!(sanitized.len() <= MAX_FILE_NAME_LEN);
+        assert_eq!(sanitized, "é".repeat(127));
+    }
 
-    Ok(id)
+    #[test]
+    fn unique_names() {
+        let mut names = UniqueNames::default();
+        let dir = Path::new("dir");
+        assert_eq!(names.claim(dir, "file.txt"), "file.txt");
+        assert_eq!(names.claim(dir, "file.txt"), "file (1).txt");
+        assert_eq!(names.claim(dir, "FILE.txt"), "FILE (2).txt");
+        assert_eq!(names.claim(dir, "folder"), "folder");
+        assert_eq!(names.claim(dir, "folder"), "folder (1)");
+        assert_eq!(names.claim(Path::new("other"), "file.txt"), "file.txt");
+    }
 }
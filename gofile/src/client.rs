@@ -1,52 +1,141 @@
+mod builder;
+mod download;
+mod walk;
+
+pub use self::builder::ClientBuilder;
+pub use self::download::Download;
+pub use self::download::DownloadOptions;
+pub use self::download::verify_file;
+pub use self::walk::WalkEntry;
+pub use self::walk::WalkOptions;
 use crate::AccountResponse;
 use crate::ApiResponse;
 use crate::Error;
+use crate::FolderInfo;
 use crate::MultipartPart;
 use crate::Page;
+use crate::PageChild;
+use crate::PageQuery;
+use crate::RetryPolicy;
 use crate::UploadInfo;
+use crate::UploadOptions;
+use crate::WebsiteTokenProvider;
+use crate::retry;
+use crate::website_token;
+use futures_util::Stream;
+use futures_util::TryStreamExt;
+use futures_util::stream;
+use reqwest::Method;
+use reqwest::RequestBuilder;
+use reqwest::StatusCode;
 use reqwest::header::AUTHORIZATION;
+use reqwest::header::USER_AGENT;
 use reqwest::multipart::Form;
 use sha2::Digest;
 use sha2::Sha256;
+use std::collections::HashSet;
 use std::sync::Arc;
-use std::time::SystemTime;
-use std::time::UNIX_EPOCH;
+use std::time::Duration;
 use tokio::sync::Semaphore;
 
-const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36";
-const LANGUAGE: &str = "en-US";
+/// Check whether another page of a folder listing needs to be fetched.
+///
+/// `seen` is the number of children received so far, including repeats,
+/// and `added` is the number of new children in the last page.
+/// The page size is not used, since the server may return fewer children per page than asked for.
+fn has_next_page(children_count: u64, seen: usize, added: usize) -> bool {
+    let seen = u64::try_from(seen).expect("children count does not fit in a u64");
 
-fn unix_epoch_secs() -> f64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("system time is before the unix epoch")
-        .as_secs_f64()
+    added != 0 && seen < children_count
 }
 
-/// See: https://github.com/yt-dlp/yt-dlp/issues/16117#issuecomment-4011609858
-#[expect(dead_code)]
-fn generate_website_token(token: &str) -> String {
-    let time_4 = (unix_epoch_secs() / f64::from(60 * 60 * 4)) as u64;
-    let hash_salt = "gf2026x";
-
-    let data = format!("{USER_AGENT}::{LANGUAGE}::{token}::{time_4}::{hash_salt}");
-
+/// Hash a folder password the same way the website does.
+fn hash_password(password: &str) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(data.as_bytes());
+    hasher.update(password.as_bytes());
     let hash = hasher.finalize();
 
     base16ct::lower::encode_string(&hash)
 }
 
+/// Read an api response, turning error statuses into errors.
+async fn read_api_response<T>(response: reqwest::Response) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    // Api errors may come with an error status code,
+    // so check the body before the status code.
+    let http_error = response.error_for_status_ref().err();
+    let body = response.bytes().await?;
+
+    let api_response: ApiResponse<serde_json::Value> = match serde_json::from_slice(&body) {
+        Ok(api_response) => api_response,
+        Err(error) => {
+            return Err(match http_error {
+                Some(http_error) if http_error.status() == Some(StatusCode::TOO_MANY_REQUESTS) => {
+                    Error::RateLimited
+                }
+                Some(http_error) => Error::Reqwest(http_error),
+                None => Error::Json(error),
+            });
+        }
+    };
+    if api_response.status != "ok" {
+        return Err(Error::from_api_status(api_response.status));
+    }
+
+    // Content responses report password problems inside an "ok" response.
+    let password_status = api_response
+        .data
+        .get("passwordStatus")
+        .and_then(|value| value.as_str());
+    match password_status {
+        Some("passwordRequired") => return Err(Error::PasswordRequired),
+        Some("passwordWrong") => return Err(Error::WrongPassword),
+        _ => {}
+    }
+
+    if let Some(http_error) = http_error {
+        return Err(Error::Reqwest(http_error));
+    }
+
+    Ok(serde_json::from_value(api_response.data)?)
+}
+
 #[derive(Debug)]
 struct Tokens {
     token: Option<String>,
-    website_token: Option<String>,
+    website_token: Option<WebsiteToken>,
+}
+
+/// A generated website token.
+#[derive(Debug)]
+struct WebsiteToken {
+    value: String,
+
+    /// The time window this was generated in.
+    ///
+    /// Website tokens are only valid until this window ends.
+    window: u64,
+}
+
+/// The client settings
+#[derive(Debug)]
+struct ClientConfig {
+    api_base_url: String,
+    upload_base_url: String,
+    website_base_url: String,
+    user_agent: String,
+    language: String,
+    timeout: Option<Duration>,
+    website_token_provider: Arc<dyn WebsiteTokenProvider>,
+    retry_policy: RetryPolicy,
 }
 
 /// The client state
 #[derive(Debug)]
 struct ClientState {
+    config: ClientConfig,
     tokens: std::sync::Mutex<Tokens>,
     get_website_token_sem: Semaphore,
 }
@@ -64,23 +153,95 @@ pub struct Client {
 impl Client {
     /// Make a new client.
     pub fn new() -> Self {
-        let client = reqwest::Client::builder()
-            .user_agent(USER_AGENT)
-            .build()
-            .expect("failed to build client");
-        let state = Arc::new(ClientState {
-            tokens: std::sync::Mutex::new(Tokens {
-                token: None,
-                website_token: None,
-            }),
-            get_website_token_sem: Semaphore::new(1),
-        });
-        Self { client, state }
+        Self::builder().build().expect("failed to build client")
+    }
+
+    /// Make a builder for a client.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Get the user agent presented to the server.
+    pub fn user_agent(&self) -> &str {
+        &self.state.config.user_agent
+    }
+
+    /// Get the language presented to the server.
+    pub fn language(&self) -> &str {
+        &self.state.config.language
+    }
+
+    /// Get the base url of the website.
+    pub fn website_base_url(&self) -> &str {
+        &self.state.config.website_base_url
+    }
+
+    /// Make a request with the configured user agent and timeout.
+    pub(crate) fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let config = &self.state.config;
+        let mut request = self
+            .client
+            .request(method, url)
+            .header(USER_AGENT, config.user_agent.as_str());
+        if let Some(timeout) = config.timeout {
+            request = request.timeout(timeout);
+        }
+        request
+    }
+
+    /// Send a request, retrying it according to the retry policy.
+    ///
+    /// Requests that are not idempotent, like POST requests, are only retried if they could not connect,
+    /// since the server may have already acted on them.
+    /// Requests with bodies that cannot be replayed, which includes all multipart uploads, are only sent once.
+    pub(crate) async fn send(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let retry_policy = &self.state.config.retry_policy;
+        let (client, request) = request.build_split();
+        let mut request = request?;
+        let is_idempotent = retry::is_idempotent(request.method());
+        let mut retry = 1;
+        loop {
+            let next_request = if retry < retry_policy.max_attempts {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = client.execute(request).await;
+            let Some(next_request) = next_request else {
+                return result;
+            };
+
+            let delay = match result.as_ref() {
+                Ok(response) if is_idempotent && retry::is_retryable_status(response.status()) => {
+                    match retry::retry_after(response) {
+                        Some(delay) if delay > retry_policy.max_retry_after => return result,
+                        Some(delay) => delay,
+                        None => retry_policy.backoff(retry),
+                    }
+                }
+                Err(error) if retry::is_retryable_error(error, is_idempotent) => {
+                    retry_policy.backoff(retry)
+                }
+                _ => return result,
+            };
+            tokio::time::sleep(delay).await;
+
+            request = next_request;
+            retry += 1;
+        }
     }
 
     /// Set the token.
     pub fn set_token(&self, token: String) {
-        self.state.tokens.lock().expect("tokens poisoned").token = Some(token);
+        let mut tokens = self.state.tokens.lock().expect("tokens poisoned");
+        // The website token is derived from the token, so it must be regenerated.
+        if tokens.token.as_ref() != Some(&token) {
+            tokens.website_token = None;
+        }
+        tokens.token = Some(token);
     }
 
     /// Get the token.
@@ -98,17 +259,11 @@ impl Client {
 
     /// Login as a guest.
     pub async fn login_guest(&self) -> Result<(), Error> {
-        let url = "https://api.gofile.io/accounts";
-        let api_response: ApiResponse<AccountResponse> = self
-            .client
-            .post(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
+        let url = format!("{}/accounts", self.state.config.api_base_url);
+        let response = self.send(self.request(Method::POST, &url)).await?;
+        let account: AccountResponse = read_api_response(response).await?;
 
-        self.set_token(api_response.data.token);
+        self.set_token(account.token);
 
         Ok(())
     }
@@ -120,106 +275,242 @@ impl Client {
             .acquire()
             .await
             .expect("sem closed");
+        let window = website_token::current_window();
         let (token, maybe_website_token) = {
             let tokens = self.state.tokens.lock().expect("tokens poisoned");
 
             (
                 tokens.token.clone().ok_or(Error::MissingToken)?,
-                tokens.website_token.clone(),
+                tokens
+                    .website_token
+                    .as_ref()
+                    .filter(|website_token| website_token.window == window)
+                    .map(|website_token| website_token.value.clone()),
             )
         };
         if let Some(website_token) = maybe_website_token {
             return Ok(website_token);
         }
 
-        let script = self
-            .client
-            .get("https://gofile.io/dist/js/wt.obf.js")
-            .send()
-            .await?
-            .error_for_status()?
-            .text()
+        let website_token = self
+            .state
+            .config
+            .website_token_provider
+            .generate(self, &token)
             .await?;
 
-        let website_token = tokio::task::spawn_blocking(move || {
-            let runtime = rquickjs::Runtime::new()?;
-            let ctx = rquickjs::Context::full(&runtime)?;
-
-            let website_token = ctx.with(|ctx| {
-                let globals = ctx.globals();
-                let navigator = rquickjs::Object::new(ctx.clone())?;
-                navigator.set("userAgent", USER_AGENT)?;
-                navigator.set("language", LANGUAGE)?;
-
-                globals.set("navigator", navigator)?;
-
-                let result: Result<(), _> = ctx.eval(script);
-                result?;
-
-                let result: Result<String, _> = ctx.eval(format!("generateWT(\"{token}\")"));
-                let website_token = result?;
-
-                Ok::<_, Error>(website_token)
-            })?;
-
-            Ok::<_, Error>(website_token)
-        })
-        .await??;
-
         self.state
             .tokens
             .lock()
             .expect("tokens poisoned")
-            .website_token = Some(website_token.clone());
+            .website_token = Some(WebsiteToken {
+            value: website_token.clone(),
+            window,
+        });
 
         drop(permit);
 
         Ok(website_token)
     }
 
-    /// Get a page.
-    pub async fn get_page(&self, id: &str) -> Result<Page, Error> {
+    /// Forget the website token, so that it is regenerated on the next request.
+    fn clear_website_token(&self) {
+        self.state
+            .tokens
+            .lock()
+            .expect("tokens poisoned")
+            .website_token = None;
+    }
+
+    /// Get a single page of a folder listing, where the first page is 1.
+    ///
+    /// Unlike [`Client::get_page_with`], the children are only those of this page.
+    /// This is useful to get the details of a folder without listing all of it.
+    /// If the server rejects the website token, it is regenerated and the request is retried once.
+    pub async fn get_page_number(
+        &self,
+        id: &str,
+        query: &PageQuery,
+        page_number: u64,
+    ) -> Result<Page, Error> {
+        match self.try_get_page_number(id, query, page_number).await {
+            Err(error) if error.is_rejected_website_token() => {
+                self.clear_website_token();
+                self.try_get_page_number(id, query, page_number).await
+            }
+            result => result,
+        }
+    }
+
+    async fn try_get_page_number(
+        &self,
+        id: &str,
+        query: &PageQuery,
+        page_number: u64,
+    ) -> Result<Page, Error> {
         // TODO: We lock client data twice here, we should probably only do so once.
         let token = self.get_token()?;
         let website_token = self.get_website_token().await?;
 
-        let page = 1;
-        let page_size = 1000;
-        let url = format!(
-            "https://api.gofile.io/contents/{id}?contentFilter=&page={page}&pageSize={page_size}&sortField=name&sortDirection=1"
-        );
-        let request = self
-            .client
-            .get(url)
+        let url = format!("{}/contents/{id}", self.state.config.api_base_url);
+        let mut request = self
+            .request(Method::GET, &url)
+            .query(&[
+                ("contentFilter", query.content_filter.as_str()),
+                ("page", &page_number.to_string()),
+                ("pageSize", &query.page_size.to_string()),
+                ("sortField", query.sort_field.as_str()),
+                ("sortDirection", query.sort_direction.as_str()),
+            ])
             .header(AUTHORIZATION, format!("Bearer {token}"));
-        let api_response: ApiResponse<Page> = request
+        if let Some(password) = query.password.as_ref() {
+            request = request.query(&[("password", hash_password(password))]);
+        }
+        let request = request
             .header("X-Website-Token", website_token)
-            .header("X-BL", LANGUAGE)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        Ok(api_response.data)
+            .header("X-BL", self.state.config.language.as_str());
+        let response = self.send(request).await?;
+        read_api_response(response).await
+    }
+
+    /// Get a page.
+    ///
+    /// This fetches every page of the folder listing,
+    /// so the returned children are complete even for large folders.
+    pub async fn get_page(&self, id: &str) -> Result<Page, Error> {
+        self.get_page_with(id, &PageQuery::default()).await
+    }
+
+    /// Get a page, using the given options to filter and sort the children.
+    pub async fn get_page_with(&self, id: &str, query: &PageQuery) -> Result<Page, Error> {
+        let mut page = self.get_page_number(id, query, 1).await?;
+        let mut seen = page.children.len();
+        let mut added = seen;
+        let mut page_number = 1;
+        while has_next_page(page.children_count, seen, added) {
+            page_number += 1;
+            let next_page = self.get_page_number(id, query, page_number).await?;
+            let old_len = page.children.len();
+            seen += next_page.children.len();
+            page.children.extend(next_page.children);
+            added = page.children.len() - old_len;
+        }
+
+        Ok(page)
+    }
+
+    /// Get a stream of the children of a page.
+    ///
+    /// Unlike [`Client::get_page`], this only keeps one page of the listing in memory at a time,
+    /// along with the ids of the children already returned.
+    pub fn get_page_children(
+        &self,
+        id: &str,
+    ) -> impl Stream<Item = Result<PageChild, Error>> + Send + 'static {
+        self.get_page_children_with(id, PageQuery::default())
+    }
+
+    /// Get a stream of the children of a page, using the given options to filter and sort them.
+    pub fn get_page_children_with(
+        &self,
+        id: &str,
+        query: PageQuery,
+    ) -> impl Stream<Item = Result<PageChild, Error>> + Send + 'static {
+        struct State {
+            client: Client,
+            id: String,
+            query: PageQuery,
+            page_number: u64,
+            seen: usize,
+            ids: HashSet<String>,
+            done: bool,
+        }
+
+        let state = State {
+            client: self.clone(),
+            id: id.to_string(),
+            query,
+            page_number: 0,
+            seen: 0,
+            ids: HashSet::new(),
+            done: false,
+        };
+        stream::try_unfold(state, |mut state| async move {
+            if state.done {
+                return Ok::<_, Error>(None);
+            }
+
+            state.page_number += 1;
+            let page = state
+                .client
+                .get_page_number(&state.id, &state.query, state.page_number)
+                .await?;
+            state.seen += page.children.len();
+
+            // Skip children repeated from earlier pages.
+            let children: Vec<_> = page
+                .children
+                .into_values()
+                .filter(|child| state.ids.insert(child.id.clone()))
+                .collect();
+            state.done = !has_next_page(page.children_count, state.seen, children.len());
+
+            Ok(Some((stream::iter(children.into_iter().map(Ok)), state)))
+        })
+        .try_flatten()
+    }
+
+    /// Create a folder inside of another folder.
+    ///
+    /// The parent folder must belong to the account of the current token.
+    pub async fn create_folder(
+        &self,
+        parent_folder_id: &str,
+        name: &str,
+    ) -> Result<FolderInfo, Error> {
+        let url = format!("{}/contents/createFolder", self.state.config.api_base_url);
+        let token = self.get_token()?;
+        let body = serde_json::json!({
+            "parentFolderId": parent_folder_id,
+            "folderName": name,
+        });
+        let request = self
+            .request(Method::POST, &url)
+            .header(AUTHORIZATION, format!("Bearer {token}"))
+            .json(&body);
+        let response = self.send(request).await?;
+        read_api_response(response).await
     }
 
     /// Upload a file
     pub async fn upload(&self, file: MultipartPart) -> Result<UploadInfo, Error> {
-        let form = Form::new().part("file", file);
+        self.upload_with(file, &UploadOptions::default()).await
+    }
+
+    /// Upload a file using the given options.
+    pub async fn upload_with(
+        &self,
+        file: MultipartPart,
+        options: &UploadOptions,
+    ) -> Result<UploadInfo, Error> {
+        let mut form = Form::new();
+        if let Some(folder_id) = options.folder_id.as_ref() {
+            form = form.text("folderId", folder_id.clone());
+        }
+        let form = form.part("file", file);
 
-        let url = "https://upload.gofile.io/uploadfile";
+        let url = format!("{}/uploadfile", self.state.config.upload_base_url);
         let token = self.get_token()?;
-        let api_response: ApiResponse<UploadInfo> = self
+
+        // Uploads may take much longer than api requests, so skip the timeout.
+        let request = self
             .client
-            .post(url)
+            .request(Method::POST, &url)
+            .header(USER_AGENT, self.user_agent())
             .header(AUTHORIZATION, format!("Bearer {token}"))
-            .multipart(form)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        Ok(api_response.data)
+            .multipart(form);
+        let response = self.send(request).await?;
+        read_api_response(response).await
     }
 }
 
@@ -228,3 +519,422 @@ impl Default for Client {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::Response;
+    use crate::test_util::SCRIPT;
+    use crate::test_util::TestServer;
+    use crate::test_util::file_json;
+    use crate::test_util::page_json;
+    use crate::test_util::test_client;
+    use crate::test_util::upload_json;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    const PAGE_SIZE: u64 = 1000;
+    const PAGE: &str = r#"{
+        "status": "ok",
+        "data": {
+            "childrenCount": 1,
+            "code": "abc123",
+            "createTime": 1700000000,
+            "id": "page-id",
+            "children": {
+                "child-id": {
+                    "createTime": 1700000000,
+                    "modTime": 1700000001,
+                    "downloadCount": 2,
+                    "link": "https://store.example/download/child-id/file.txt",
+                    "md5": "5d41402abc4b2a76b9719d911017c592",
+                    "size": 5,
+                    "name": "file.txt",
+                    "type": "file",
+                    "id": "child-id"
+                }
+            },
+            "totalSize": 5,
+            "modTime": 1700000001,
+            "name": "folder",
+            "public": true,
+            "totalDownloadCount": 2
+        }
+    }"#;
+
+    /// Make a page response with `len` file children, starting at index `start`.
+    fn paginated_page_json(children_count: u64, start: u64, len: u64) -> String {
+        let children: Vec<String> = (start..start + len)
+            .map(|i| file_json(&format!("child-{i}"), &format!("file-{i}.txt")))
+            .collect();
+        page_json("abc123", "page-id", children_count, &children)
+    }
+
+    /// Make a server that serves a folder with `children_count` children.
+    async fn paginated_server(children_count: u64) -> TestServer {
+        paginated_server_with(children_count, PAGE_SIZE, false).await
+    }
+
+    /// Make a server that serves a folder with `children_count` children, `page_size` at a time.
+    ///
+    /// If `repeat` is true, every page has the same children as the first.
+    async fn paginated_server_with(
+        children_count: u64,
+        page_size: u64,
+        repeat: bool,
+    ) -> TestServer {
+        TestServer::new(move |request| {
+            if request.path == "/dist/js/wt.obf.js" {
+                return Response::new(200, SCRIPT);
+            }
+
+            let page_number: u64 = request
+                .path
+                .split(['?', '&'])
+                .find_map(|pair| pair.strip_prefix("page="))
+                .expect("missing page number")
+                .parse()
+                .expect("invalid page number");
+            let start = if repeat {
+                0
+            } else {
+                (page_number - 1) * page_size
+            };
+            let len = children_count.saturating_sub(start).min(page_size);
+            Response::json(paginated_page_json(children_count, start, len))
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_page_fetches_all_pages() {
+        let server = paginated_server(2 * PAGE_SIZE + 1).await;
+        let client = test_client(&server);
+
+        let page = client.get_page("abc123").await.expect("failed to get page");
+        assert_eq!(
+            u64::try_from(page.children.len()).unwrap(),
+            page.children_count
+        );
+        assert!(page.children.contains_key("child-2000"));
+    }
+
+    #[tokio::test]
+    async fn get_page_number_fetches_one_page() {
+        let server = paginated_server(2 * PAGE_SIZE + 1).await;
+        let client = test_client(&server);
+
+        let page = client
+            .get_page_number("abc123", &PageQuery::default(), 2)
+            .await
+            .expect("failed to get page");
+        assert_eq!(u64::try_from(page.children.len()).unwrap(), PAGE_SIZE);
+        assert!(page.children.contains_key("child-1000"));
+    }
+
+    #[tokio::test]
+    async fn get_page_follows_smaller_server_page_size() {
+        let server = paginated_server_with(250, 100, false).await;
+        let client = test_client(&server);
+
+        let page = client.get_page("abc123").await.expect("failed to get page");
+        assert_eq!(page.children.len(), 250);
+
+        let children: Vec<PageChild> = client
+            .get_page_children("abc123")
+            .try_collect()
+            .await
+            .expect("failed to get page children");
+        assert_eq!(children.len(), 250);
+    }
+
+    #[tokio::test]
+    async fn get_page_stops_on_repeated_pages() {
+        let server = paginated_server_with(250, 100, true).await;
+        let client = test_client(&server);
+
+        let page = client.get_page("abc123").await.expect("failed to get page");
+        assert_eq!(page.children.len(), 100);
+
+        let children: Vec<PageChild> = client
+            .get_page_children("abc123")
+            .try_collect()
+            .await
+            .expect("failed to get page children");
+        assert_eq!(children.len(), 100);
+    }
+
+    #[tokio::test]
+    async fn get_page_children_streams_all_pages() {
+        let server = paginated_server(PAGE_SIZE + 5).await;
+        let client = test_client(&server);
+
+        let children: Vec<PageChild> = client
+            .get_page_children("abc123")
+            .try_collect()
+            .await
+            .expect("failed to get page children");
+        assert_eq!(u64::try_from(children.len()).unwrap(), PAGE_SIZE + 5);
+    }
+
+    #[tokio::test]
+    async fn get_page_with_query() {
+        let server = TestServer::new(|request| {
+            if request.path == "/dist/js/wt.obf.js" {
+                return Response::new(200, SCRIPT);
+            }
+
+            let query = request.path.split_once('?').expect("missing query").1;
+            assert_eq!(
+                query,
+                "contentFilter=big+file&page=1&pageSize=2&sortField=size&sortDirection=-1"
+            );
+            // The server returns children in sorted order,
+            // so make sure that order is not lost.
+            let mut json = paginated_page_json(2, 0, 2);
+            json = json.replace("child-0", "child-tmp");
+            json = json.replace("child-1", "child-0");
+            json = json.replace("child-tmp", "child-1");
+            Response::json(json)
+        })
+        .await;
+        let client = test_client(&server);
+
+        let query = PageQuery {
+            content_filter: "big file".into(),
+            sort_field: crate::SortField::Size,
+            sort_direction: crate::SortDirection::Descending,
+            page_size: 2,
+            ..PageQuery::default()
+        };
+        let page = client
+            .get_page_with("abc123", &query)
+            .await
+            .expect("failed to get page");
+        let ids: Vec<&str> = page.children.keys().map(|id| id.as_str()).collect();
+        assert_eq!(ids, ["child-1", "child-0"]);
+    }
+
+    #[tokio::test]
+    async fn get_page_with_password() {
+        let server = TestServer::new(|request| {
+            if request.path == "/dist/js/wt.obf.js" {
+                return Response::new(200, SCRIPT);
+            }
+
+            let password = request
+                .path
+                .split(['?', '&'])
+                .find_map(|pair| pair.strip_prefix("password="));
+            match password {
+                None => Response::json(r#"{"status":"error-passwordRequired","data":{}}"#),
+                Some(password) if password == hash_password("hunter2") => {
+                    Response::json(paginated_page_json(0, 0, 0))
+                }
+                Some(_) => Response::json(
+                    r#"{"status":"ok","data":{"password":true,"passwordStatus":"passwordWrong"}}"#,
+                ),
+            }
+        })
+        .await;
+        let client = test_client(&server);
+
+        let error = client.get_page("abc123").await.unwrap_err();
+        assert!(matches!(error, Error::PasswordRequired), "{error:?}");
+
+        let mut query = PageQuery {
+            password: Some("wrong".into()),
+            ..PageQuery::default()
+        };
+        let error = client.get_page_with("abc123", &query).await.unwrap_err();
+        assert!(matches!(error, Error::WrongPassword), "{error:?}");
+
+        query.password = Some("hunter2".into());
+        client
+            .get_page_with("abc123", &query)
+            .await
+            .expect("failed to get page");
+    }
+
+    #[tokio::test]
+    async fn api_error_statuses() {
+        let server = TestServer::new(|request| match request.path.as_str() {
+            "/dist/js/wt.obf.js" => Response::new(200, SCRIPT),
+            path if path.starts_with("/contents/gone?") => {
+                Response::json(r#"{"status":"error-notFound","data":{}}"#).status(404)
+            }
+            path if path.starts_with("/contents/premium?") => {
+                Response::json(r#"{"status":"error-notPremium","data":{}}"#)
+            }
+            path if path.starts_with("/contents/new?") => {
+                Response::json(r#"{"status":"error-somethingNew","data":{}}"#)
+            }
+            _ => Response::new(429, "slow down"),
+        })
+        .await;
+        let client = test_client(&server);
+
+        let error = client.get_page("gone").await.unwrap_err();
+        assert!(matches!(error, Error::NotFound), "{error:?}");
+
+        let error = client.get_page("premium").await.unwrap_err();
+        assert!(matches!(error, Error::NotPremium), "{error:?}");
+
+        let error = client.get_page("new").await.unwrap_err();
+        assert!(
+            matches!(&error, Error::Api { status } if status == "error-somethingNew"),
+            "{error:?}"
+        );
+
+        let error = client.login_guest().await.unwrap_err();
+        assert!(matches!(error, Error::RateLimited), "{error:?}");
+    }
+
+    #[tokio::test]
+    async fn get_page_regenerates_rejected_website_token() {
+        let script_requests = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let script_requests = script_requests.clone();
+            TestServer::new(move |request| {
+                if request.path == "/dist/js/wt.obf.js" {
+                    script_requests.fetch_add(1, Ordering::SeqCst);
+                    return Response::new(200, SCRIPT);
+                }
+
+                if script_requests.load(Ordering::SeqCst) < 2 {
+                    Response::json(r#"{"status":"error-wrongToken","data":{}}"#).status(401)
+                } else {
+                    Response::json(paginated_page_json(0, 0, 0))
+                }
+            })
+            .await
+        };
+        let client = test_client(&server);
+
+        client.get_page("abc123").await.expect("failed to get page");
+        assert_eq!(script_requests.load(Ordering::SeqCst), 2);
+
+        // The regenerated token should be reused.
+        client.get_page("abc123").await.expect("failed to get page");
+        assert_eq!(script_requests.load(Ordering::SeqCst), 2);
+
+        // Changing the token should invalidate the website token.
+        client.set_token("other-token".into());
+        client.get_page("abc123").await.expect("failed to get page");
+        assert_eq!(script_requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn builder_overrides() {
+        let server =
+            TestServer::new(
+                |request| match (request.method.as_str(), request.path.as_str()) {
+                    ("POST", "/accounts") => {
+                        assert_eq!(request.header("user-agent"), Some("test-agent"));
+                        Response::json(r#"{"status":"ok","data":{"token":"guest-token"}}"#)
+                    }
+                    ("GET", "/dist/js/wt.obf.js") => Response::new(200, SCRIPT),
+                    ("GET", path) if path.starts_with("/contents/abc123?") => {
+                        assert_eq!(request.header("authorization"), Some("Bearer guest-token"));
+                        assert_eq!(request.header("x-bl"), Some("test-lang"));
+                        assert_eq!(
+                            request.header("x-website-token"),
+                            Some("test-agent|test-lang|guest-token")
+                        );
+                        Response::json(PAGE)
+                    }
+                    _ => Response::new(404, ""),
+                },
+            )
+            .await;
+
+        let client = Client::builder()
+            .api_base_url(format!("{}/", server.url()))
+            .website_base_url(server.url())
+            .user_agent_and_language("test-agent", "test-lang")
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("failed to build client");
+        client.login_guest().await.expect("failed to log in");
+        assert_eq!(client.get_token().unwrap(), "guest-token");
+
+        let page = client.get_page("abc123").await.expect("failed to get page");
+        assert_eq!(page.code, "abc123");
+        assert_eq!(page.children["child-id"].name, "file.txt");
+    }
+
+    #[tokio::test]
+    async fn builder_prebuilt_client() {
+        let server = TestServer::new(|request| {
+            assert_eq!(request.header("user-agent"), Some("test-agent"));
+            Response::json(r#"{"status":"ok","data":{"token":"guest-token"}}"#)
+        })
+        .await;
+
+        let client = Client::builder()
+            .client(reqwest::Client::new())
+            .api_base_url(server.url())
+            .user_agent_and_language("test-agent", "test-lang")
+            .build()
+            .expect("failed to build client");
+        client.login_guest().await.expect("failed to log in");
+        assert_eq!(client.get_token().unwrap(), "guest-token");
+    }
+
+    #[tokio::test]
+    async fn upload_with_folder_id() {
+        let server = TestServer::new(|request| {
+            assert_eq!(request.path, "/uploadfile");
+            assert_eq!(request.header("authorization"), Some("Bearer guest-token"));
+
+            let body = String::from_utf8_lossy(&request.body);
+            let has_folder_id = body.contains("name=\"folderId\"\r\n\r\nfolder-id\r\n");
+            let folder_id = if has_folder_id { "folder-id" } else { "new-id" };
+            Response::json(upload_json("file.txt", folder_id, "code"))
+        })
+        .await;
+        let client = test_client(&server);
+
+        let part = MultipartPart::text("hello").file_name("file.txt");
+        let upload_info = client.upload(part).await.expect("failed to upload");
+        assert_eq!(upload_info.parent_folder, "new-id");
+
+        let part = MultipartPart::text("hello").file_name("file.txt");
+        let options = UploadOptions {
+            folder_id: Some("folder-id".into()),
+        };
+        let upload_info = client
+            .upload_with(part, &options)
+            .await
+            .expect("failed to upload");
+        assert_eq!(upload_info.parent_folder, "folder-id");
+    }
+
+    #[tokio::test]
+    async fn create_folder() {
+        let server = TestServer::new(|request| {
+            assert_eq!(request.method, "POST");
+            assert_eq!(request.path, "/contents/createFolder");
+            assert_eq!(request.header("authorization"), Some("Bearer guest-token"));
+
+            let body: serde_json::Value =
+                serde_json::from_slice(&request.body).expect("invalid body");
+            assert_eq!(body["parentFolderId"], "parent-id");
+            Response::json(format!(
+                r#"{{"status":"ok","data":{{"id":"folder-id","code":"folder-code","name":{},"parentFolder":"parent-id","type":"folder"}}}}"#,
+                body["folderName"]
+            ))
+        })
+        .await;
+        let client = test_client(&server);
+
+        let folder = client
+            .create_folder("parent-id", "sub")
+            .await
+            .expect("failed to create folder");
+        assert_eq!(folder.id, "folder-id");
+        assert_eq!(folder.code, "folder-code");
+        assert_eq!(folder.name, "sub");
+        assert_eq!(folder.parent_folder, "parent-id");
+    }
+}
@@ -0,0 +1,178 @@
+use super::Client;
+use super::ClientConfig;
+use super::ClientState;
+use super::Tokens;
+use crate::Error;
+use crate::RemoteScriptWebsiteTokenProvider;
+use crate::RetryPolicy;
+use crate::WebsiteTokenProvider;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+pub(crate) const DEFAULT_API_BASE_URL: &str = "https://api.gofile.io";
+pub(crate) const DEFAULT_UPLOAD_BASE_URL: &str = "https://upload.gofile.io";
+pub(crate) const DEFAULT_WEBSITE_BASE_URL: &str = "https://gofile.io";
+pub(crate) const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36";
+pub(crate) const DEFAULT_LANGUAGE: &str = "en-US";
+
+/// A builder for a [`Client`].
+#[derive(Debug)]
+pub struct ClientBuilder {
+    client: Option<reqwest::Client>,
+    api_base_url: String,
+    upload_base_url: String,
+    website_base_url: String,
+    user_agent: String,
+    language: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    website_token_provider: Arc<dyn WebsiteTokenProvider>,
+    retry_policy: RetryPolicy,
+}
+
+impl ClientBuilder {
+    /// Make a new builder with the default settings.
+    pub fn new() -> Self {
+        Self {
+            client: None,
+            api_base_url: DEFAULT_API_BASE_URL.into(),
+            upload_base_url: DEFAULT_UPLOAD_BASE_URL.into(),
+            website_base_url: DEFAULT_WEBSITE_BASE_URL.into(),
+            user_agent: DEFAULT_USER_AGENT.into(),
+            language: DEFAULT_LANGUAGE.into(),
+            timeout: None,
+            connect_timeout: None,
+            website_token_provider: Arc::new(RemoteScriptWebsiteTokenProvider),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Use a pre-built http client.
+    ///
+    /// If this is set, the connect timeout is ignored,
+    /// as it can only be set when building the http client.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Set the base url of the api.
+    ///
+    /// Defaults to `https://api.gofile.io`.
+    pub fn api_base_url(mut self, url: impl Into<String>) -> Self {
+        self.api_base_url = trim_base_url(url.into());
+        self
+    }
+
+    /// Set the base url of the upload server.
+    ///
+    /// Defaults to `https://upload.gofile.io`.
+    pub fn upload_base_url(mut self, url: impl Into<String>) -> Self {
+        self.upload_base_url = trim_base_url(url.into());
+        self
+    }
+
+    /// Set the base url of the website.
+    ///
+    /// This is used to fetch the website token script.
+    /// Defaults to `https://gofile.io`.
+    pub fn website_base_url(mut self, url: impl Into<String>) -> Self {
+        self.website_base_url = trim_base_url(url.into());
+        self
+    }
+
+    /// Set the user agent and language to present to the server.
+    ///
+    /// These are set together as the website token is derived from both,
+    /// and the server expects them to match the values sent with each request.
+    pub fn user_agent_and_language(
+        mut self,
+        user_agent: impl Into<String>,
+        language: impl Into<String>,
+    ) -> Self {
+        self.user_agent = user_agent.into();
+        self.language = language.into();
+        self
+    }
+
+    /// Set how website tokens are generated.
+    ///
+    /// Defaults to [`RemoteScriptWebsiteTokenProvider`].
+    pub fn website_token_provider<P>(mut self, provider: P) -> Self
+    where
+        P: WebsiteTokenProvider + 'static,
+    {
+        self.website_token_provider = Arc::new(provider);
+        self
+    }
+
+    /// Set how requests that fail for transient reasons are retried.
+    ///
+    /// Defaults to [`RetryPolicy::default`].
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set the timeout for each request.
+    ///
+    /// This does not apply to file downloads or uploads, which may take much longer.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the connect timeout.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Build the client.
+    pub fn build(self) -> Result<Client, Error> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = reqwest::Client::builder().user_agent(&self.user_agent);
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                builder.build()?
+            }
+        };
+        let config = ClientConfig {
+            api_base_url: self.api_base_url,
+            upload_base_url: self.upload_base_url,
+            website_base_url: self.website_base_url,
+            user_agent: self.user_agent,
+            language: self.language,
+            timeout: self.timeout,
+            website_token_provider: self.website_token_provider,
+            retry_policy: self.retry_policy,
+        };
+        let state = Arc::new(ClientState {
+            config,
+            tokens: std::sync::Mutex::new(Tokens {
+                token: None,
+                website_token: None,
+            }),
+            get_website_token_sem: Semaphore::new(1),
+        });
+
+        Ok(Client { client, state })
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn trim_base_url(mut url: String) -> String {
+    while url.ends_with('/') {
+        url.pop();
+    }
+    url
+}
@@ -0,0 +1,912 @@
+use super::Client;
+use crate::Error;
+use crate::PageChild;
+use bytes::Bytes;
+use futures_util::Stream;
+use futures_util::StreamExt;
+use futures_util::stream::BoxStream;
+use md5::Digest;
+use md5::Md5;
+use reqwest::Method;
+use reqwest::RequestBuilder;
+use reqwest::StatusCode;
+use reqwest::header::CONTENT_RANGE;
+use reqwest::header::COOKIE;
+use reqwest::header::RANGE;
+use reqwest::header::USER_AGENT;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::AsyncSeekExt;
+use tokio::io::AsyncWriteExt;
+
+type ProgressFn = dyn Fn(u64) + Send + Sync;
+
+/// Options for downloading a file to a path.
+#[derive(Clone)]
+pub struct DownloadOptions {
+    progress: Option<Arc<ProgressFn>>,
+    resume: bool,
+    connections: u32,
+}
+
+impl DownloadOptions {
+    /// Make new download options with the default settings.
+    pub fn new() -> Self {
+        Self {
+            progress: None,
+            resume: true,
+            connections: 1,
+        }
+    }
+
+    /// Set whether to resume from an existing ".part" file.
+    ///
+    /// If the server does not support resuming, the download restarts from the beginning.
+    /// Defaults to true.
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Set the number of connections to download a file with.
+    ///
+    /// Each connection downloads a different part of the file at the same time.
+    /// Downloads with more than 1 connection cannot be resumed,
+    /// and use a single connection if the server does not support range requests.
+    /// A value of 0 is treated as 1.
+    /// Defaults to 1.
+    pub fn connections(mut self, connections: u32) -> Self {
+        self.connections = connections;
+        self
+    }
+
+    /// Set a function to call with the number of bytes written, after each write.
+    pub fn progress<F>(mut self, progress: F) -> Self
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for DownloadOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadOptions")
+            .field("progress", &self.progress.is_some())
+            .field("resume", &self.resume)
+            .field("connections", &self.connections)
+            .finish()
+    }
+}
+
+/// A stream of the bytes of a file being downloaded.
+///
+/// The md5 hash and size of the file are checked once the stream ends.
+/// If they do not match, the last item of the stream is an error.
+pub struct Download {
+    stream: BoxStream<'static, reqwest::Result<Bytes>>,
+    hasher: Md5,
+    expected_md5: Option<String>,
+    expected_size: Option<u64>,
+    size: u64,
+    done: bool,
+}
+
+impl Download {
+    /// Make a new download from a response.
+    ///
+    /// The hasher and size should describe any data that was downloaded before this response.
+    fn new(child: &PageChild, response: reqwest::Response, hasher: Md5, size: u64) -> Self {
+        Self {
+            stream: response.bytes_stream().boxed(),
+            hasher,
+            expected_md5: child.md5.clone(),
+            expected_size: child.size,
+            size,
+            done: false,
+        }
+    }
+
+    /// Make a new download for data that was already completely downloaded.
+    fn new_empty(child: &PageChild, hasher: Md5, size: u64) -> Self {
+        Self {
+            stream: futures_util::stream::empty().boxed(),
+            hasher,
+            expected_md5: child.md5.clone(),
+            expected_size: child.size,
+            size,
+            done: false,
+        }
+    }
+
+    /// Check the downloaded data against the expected md5 hash and size.
+    fn verify(&mut self) -> Result<(), Error> {
+        if let Some(expected) = self.expected_size
+            && expected != self.size
+        {
+            return Err(Error::SizeMismatch {
+                expected,
+                actual: self.size,
+            });
+        }
+
+        let actual = base16ct::lower::encode_string(&self.hasher.finalize_reset());
+        if let Some(expected) = self.expected_md5.as_ref()
+            && !expected.eq_ignore_ascii_case(&actual)
+        {
+            return Err(Error::Md5Mismatch {
+                expected: expected.clone(),
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Stream for Download {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        match this.stream.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                this.hasher.update(&chunk);
+                this.size += u64::try_from(chunk.len()).expect("chunk len does not fit in a u64");
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Some(Err(error))) => {
+                this.done = true;
+                Poll::Ready(Some(Err(Error::Reqwest(error))))
+            }
+            Poll::Ready(None) => {
+                this.done = true;
+                match this.verify() {
+                    Ok(()) => Poll::Ready(None),
+                    Err(error) => Poll::Ready(Some(Err(error))),
+                }
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl std::fmt::Debug for Download {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Download")
+            .field("expected_md5", &self.expected_md5)
+            .field("expected_size", &self.expected_size)
+            .field("size", &self.size)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl Client {
+    /// Make a request for a file's data.
+    fn download_request(&self, child: &PageChild) -> Result<RequestBuilder, Error> {
+        let link = child.link.as_ref().ok_or(Error::NotAFile)?;
+        let token = self.get_token()?;
+
+        // Downloads may take much longer than api requests, so skip the timeout.
+        Ok(self
+            .client
+            .request(Method::GET, link)
+            .header(USER_AGENT, self.user_agent())
+            .header(COOKIE, format!("accountToken={token}")))
+    }
+
+    /// Start downloading a file.
+    ///
+    /// The returned stream checks the md5 hash and size of the file once it ends.
+    pub async fn download(&self, child: &PageChild) -> Result<Download, Error> {
+        let request = self.download_request(child)?;
+        let response = self.send(request).await?.error_for_status()?;
+
+        Ok(Download::new(child, response, Md5::new(), 0))
+    }
+
+    /// Download a file to the given path.
+    ///
+    /// The file is first written to a temporary file next to the path with a ".part" extension,
+    /// which is renamed to the path once the download is verified.
+    /// If verification fails, the temporary file is removed.
+    pub async fn download_to_path(
+        &self,
+        child: &PageChild,
+        path: &Path,
+        options: &DownloadOptions,
+    ) -> Result<(), Error> {
+        let temp_path = path.with_added_extension("part");
+
+        if options.connections > 1
+            && let Some(size) = child.size
+            && size >= u64::from(options.connections)
+        {
+            return self
+                .download_to_path_segmented(child, path, &temp_path, options, size)
+                .await;
+        }
+
+        let request = self.download_request(child)?;
+        let (file, hasher, offset) =
+            open_part_file(temp_path.clone(), options.resume, child.size).await?;
+        let file = tokio::fs::File::from_std(file);
+
+        let is_complete = offset > 0
+            && Some(offset) == child.size
+            && child.md5.as_ref().is_some_and(|expected| {
+                expected.eq_ignore_ascii_case(&base16ct::lower::encode_string(
+                    &hasher.clone().finalize(),
+                ))
+            });
+        let download = if is_complete {
+            // The data was already downloaded, it just needs to be renamed.
+            Download::new_empty(child, hasher, offset)
+        } else if offset > 0 && Some(offset) != child.size {
+            let request = request.header(RANGE, format!("bytes={offset}-"));
+            let response = self.send(request).await?.error_for_status()?;
+            if is_range_response(&response, offset) {
+                Download::new(child, response, hasher, offset)
+            } else {
+                // The server ignored the range, so start over.
+                file.set_len(0).await?;
+                Download::new(child, response, Md5::new(), 0)
+            }
+        } else {
+            file.set_len(0).await?;
+            let response = self.send(request).await?.error_for_status()?;
+            Download::new(child, response, Md5::new(), 0)
+        };
+
+        if let Some(progress) = options.progress.as_ref()
+            && download.size > 0
+        {
+            progress(download.size);
+        }
+
+        write_download(file, download, &temp_path, path, options).await
+    }
+
+    /// Download a file to the given path, using multiple connections.
+    ///
+    /// This falls back to a single connection if the server does not support range requests.
+    async fn download_to_path_segmented(
+        &self,
+        child: &PageChild,
+        path: &Path,
+        temp_path: &Path,
+        options: &DownloadOptions,
+        size: u64,
+    ) -> Result<(), Error> {
+        // The file is filled out of order, so it cannot be resumed from.
+        let (file, _hasher, _offset) = open_part_file(temp_path.into(), false, Some(size)).await?;
+
+        // Every write goes through the locked handle, since on some platforms,
+        // like Windows, the lock also blocks other handles in this process.
+        let file = Arc::new(file);
+
+        let segments = split_segments(size, options.connections);
+        let (first_start, first_end) = segments[0];
+        let request = self
+            .download_request(child)?
+            .header(RANGE, format!("bytes={first_start}-{first_end}"));
+        let response = self.send(request).await?.error_for_status()?;
+        if !is_range_response(&response, first_start) {
+            return write_unranged(file, child, response, temp_path, path, options).await;
+        }
+
+        // Every range is checked before writing anything,
+        // so the file can still be downloaded over one connection if any is ignored.
+        let other_requests = segments[1..].iter().map(|&(start, end)| async move {
+            let request = self
+                .download_request(child)?
+                .header(RANGE, format!("bytes={start}-{end}"));
+            Ok::<_, Error>(self.send(request).await?.error_for_status()?)
+        });
+        let mut other_responses = futures_util::future::try_join_all(other_requests).await?;
+        if let Some(index) = segments[1..]
+            .iter()
+            .zip(&other_responses)
+            .position(|(&(start, _), response)| !is_range_response(response, start))
+        {
+            let response = other_responses.swap_remove(index);
+            return write_unranged(file, child, response, temp_path, path, options).await;
+        }
+        file.set_len(size)?;
+
+        let first_segment = write_segment(&file, first_start, first_end, response, options);
+        let other_segments = segments[1..]
+            .iter()
+            .zip(other_responses)
+            .map(|(&(start, end), response)| write_segment(&file, start, end, response, options));
+        futures_util::future::try_join(
+            first_segment,
+            futures_util::future::try_join_all(other_segments),
+        )
+        .await?;
+
+        let (file, hasher) = tokio::task::spawn_blocking(move || {
+            file.sync_all()?;
+            let hasher = hash_file(&file)?;
+            Ok::<_, Error>((file, hasher))
+        })
+        .await??;
+        let download = Download::new_empty(child, hasher, size);
+        write_download(into_tokio_file(file), download, temp_path, path, options).await
+    }
+}
+
+/// Check a local file against the md5 hash and size of a page child.
+///
+/// This returns [`Error::SizeMismatch`] or [`Error::Md5Mismatch`] if the file does not match.
+pub async fn verify_file(child: &PageChild, path: &Path) -> Result<(), Error> {
+    let size = tokio::fs::metadata(path).await?.len();
+    if let Some(expected) = child.size
+        && expected != size
+    {
+        return Err(Error::SizeMismatch {
+            expected,
+            actual: size,
+        });
+    }
+
+    let hasher = {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || hash_file(&std::fs::File::open(path)?)).await??
+    };
+    Download::new_empty(child, hasher, size).verify()
+}
+
+/// Hash the contents of a file, from the start.
+///
+/// This blocks, so it should be run with `spawn_blocking`.
+fn hash_file(mut file: &std::fs::File) -> Result<Md5, Error> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut hasher = Md5::new();
+    std::io::copy(
+        &mut std::io::BufReader::new(file),
+        &mut HashWriter(&mut hasher),
+    )?;
+    Ok(hasher)
+}
+
+/// Turn a shared file handle back into an async one, once nothing else uses it.
+fn into_tokio_file(file: Arc<std::fs::File>) -> tokio::fs::File {
+    let file = Arc::into_inner(file).expect("file handle is still shared");
+    tokio::fs::File::from_std(file)
+}
+
+/// Write a whole buffer at an offset, without using the file position.
+///
+/// This lets many tasks write to the same handle at once.
+/// This blocks, so it should be run with `spawn_blocking`.
+fn write_all_at(file: &std::fs::File, mut buf: &[u8], mut offset: u64) -> std::io::Result<()> {
+    while !buf.is_empty() {
+        #[cfg(unix)]
+        let n = std::os::unix::fs::FileExt::write_at(file, buf, offset)?;
+        #[cfg(windows)]
+        let n = std::os::windows::fs::FileExt::seek_write(file, buf, offset)?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        buf = &buf[n..];
+        offset += u64::try_from(n).expect("write len does not fit in a u64");
+    }
+
+    Ok(())
+}
+
+/// Open and lock a ".part" file.
+///
+/// If resuming, the existing data is hashed and its length is returned.
+/// Otherwise, the returned length is 0 and the file should be truncated before writing.
+async fn open_part_file(
+    temp_path: PathBuf,
+    resume: bool,
+    expected_size: Option<u64>,
+) -> Result<(std::fs::File, Md5, u64), Error> {
+    tokio::task::spawn_blocking(move || {
+        let mut file = std::fs::File::options()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .open(&temp_path)?;
+        file.try_lock().map_err(std::io::Error::from)?;
+
+        let mut hasher = Md5::new();
+        let offset = file.metadata()?.len();
+        let can_resume = resume && expected_size.is_some_and(|size| offset <= size);
+        if !can_resume {
+            return Ok((file, hasher, 0));
+        }
+
+        let mut reader = std::io::BufReader::new(&mut file);
+        std::io::copy(&mut reader, &mut HashWriter(&mut hasher))?;
+
+        Ok((file, hasher, offset))
+    })
+    .await?
+}
+
+/// Write a whole file from a response that ignored the requested range.
+async fn write_unranged(
+    file: Arc<std::fs::File>,
+    child: &PageChild,
+    response: reqwest::Response,
+    temp_path: &Path,
+    path: &Path,
+    options: &DownloadOptions,
+) -> Result<(), Error> {
+    let file = into_tokio_file(file);
+    file.set_len(0).await?;
+    let download = Download::new(child, response, Md5::new(), 0);
+    write_download(file, download, temp_path, path, options).await
+}
+
+/// Write a download to the end of a ".part" file, then rename it once verified.
+async fn write_download(
+    mut file: tokio::fs::File,
+    mut download: Download,
+    temp_path: &Path,
+    path: &Path,
+    options: &DownloadOptions,
+) -> Result<(), Error> {
+    file.seek(SeekFrom::Start(download.size)).await?;
+
+    while let Some(chunk) = download.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(error @ (Error::Md5Mismatch { .. } | Error::SizeMismatch { .. })) => {
+                drop(file);
+                tokio::fs::remove_file(temp_path).await?;
+                return Err(error);
+            }
+            Err(error) => return Err(error),
+        };
+
+        file.write_all(&chunk).await?;
+        if let Some(progress) = options.progress.as_ref() {
+            progress(u64::try_from(chunk.len()).expect("chunk len does not fit in a u64"));
+        }
+    }
+
+    file.flush().await?;
+    file.sync_all().await?;
+    tokio::fs::rename(temp_path, path).await?;
+    drop(file);
+
+    Ok(())
+}
+
+/// Write the response to a range request into a ".part" file at the given offset.
+async fn write_segment(
+    file: &Arc<std::fs::File>,
+    start: u64,
+    end: u64,
+    response: reqwest::Response,
+    options: &DownloadOptions,
+) -> Result<(), Error> {
+    let expected = end - start + 1;
+    let mut actual = 0;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        let len = u64::try_from(chunk.len()).expect("chunk len does not fit in a u64");
+        actual += len;
+        if actual > expected {
+            break;
+        }
+
+        let offset = start + actual - len;
+        let file = file.clone();
+        tokio::task::spawn_blocking(move || write_all_at(&file, &chunk, offset)).await??;
+        if let Some(progress) = options.progress.as_ref() {
+            progress(len);
+        }
+    }
+    if actual != expected {
+        return Err(Error::SizeMismatch { expected, actual });
+    }
+
+    Ok(())
+}
+
+/// Split a file into inclusive byte ranges, one for each connection.
+fn split_segments(size: u64, connections: u32) -> Vec<(u64, u64)> {
+    let connections = u64::from(connections);
+    let segment_size = size.div_ceil(connections);
+
+    (0..connections)
+        .map(|i| i * segment_size)
+        .take_while(|&start| start < size)
+        .map(|start| (start, (start + segment_size).min(size) - 1))
+        .collect()
+}
+
+/// Check if a response to a range request starts at the given offset.
+fn is_range_response(response: &reqwest::Response, offset: u64) -> bool {
+    if response.status() != StatusCode::PARTIAL_CONTENT {
+        return false;
+    }
+
+    response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("bytes "))
+        .and_then(|value| value.split_once('-'))
+        .is_some_and(|(start, _)| start.parse::<u64>().ok() == Some(offset))
+}
+
+/// A writer that feeds everything written to it into a hasher.
+struct HashWriter<'a>(&'a mut Md5);
+
+impl std::io::Write for HashWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::Response;
+    use crate::test_util::TestServer;
+    use crate::test_util::test_client;
+    use futures_util::TryStreamExt;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+
+    const DATA: &str = "hello";
+    const DATA_MD5: &str = "5d41402abc4b2a76b9719d911017c592";
+
+    fn child(server: &TestServer, md5: &str) -> PageChild {
+        let json = format!(
+            r#"{{
+                "createTime": 1700000000,
+                "modTime": 1700000001,
+                "link": "{}/download/file.txt",
+                "md5": "{md5}",
+                "size": 5,
+                "name": "file.txt",
+                "type": "file",
+                "id": "child-id"
+            }}"#,
+            server.url()
+        );
+        serde_json::from_str(&json).expect("invalid child")
+    }
+
+    async fn file_server() -> TestServer {
+        TestServer::new(|request| {
+            assert_eq!(request.header("cookie"), Some("accountToken=guest-token"));
+            Response::new(200, DATA)
+        })
+        .await
+    }
+
+    /// Make a file server that supports range requests,
+    /// recording the range header of each request.
+    async fn range_file_server() -> (TestServer, Arc<std::sync::Mutex<Vec<Option<String>>>>) {
+        let ranges = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server = {
+            let ranges = ranges.clone();
+            TestServer::new(move |request| {
+                let range = request.header("range").map(String::from);
+                ranges.lock().unwrap().push(range.clone());
+
+                let Some(range) = range else {
+                    return Response::new(200, DATA);
+                };
+                let (start, end) = range
+                    .strip_prefix("bytes=")
+                    .and_then(|range| range.split_once('-'))
+                    .expect("invalid range");
+                let len = DATA.len();
+                let start: usize = start.parse().expect("invalid range start");
+                let end: usize = match end {
+                    "" => len - 1,
+                    end => end.parse().expect("invalid range end"),
+                };
+                Response::new(206, &DATA.as_bytes()[start..=end])
+                    .header("Content-Range", &format!("bytes {start}-{end}/{len}"))
+            })
+            .await
+        };
+        (server, ranges)
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("gofile-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("failed to create temp dir");
+        path
+    }
+
+    #[tokio::test]
+    async fn download_stream() {
+        let server = file_server().await;
+        let client = test_client(&server);
+
+        let chunks: Vec<Bytes> = client
+            .download(&child(&server, DATA_MD5))
+            .await
+            .expect("failed to start download")
+            .try_collect()
+            .await
+            .expect("failed to download");
+        assert_eq!(chunks.concat(), DATA.as_bytes());
+
+        let error = client
+            .download(&child(&server, "00000000000000000000000000000000"))
+            .await
+            .expect("failed to start download")
+            .try_collect::<Vec<Bytes>>()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Md5Mismatch { .. }), "{error:?}");
+    }
+
+    #[tokio::test]
+    async fn download_to_path() {
+        let server = file_server().await;
+        let client = test_client(&server);
+        let dir = temp_dir("download-to-path");
+        let path = dir.join("file.txt");
+
+        let progress = Arc::new(AtomicU64::new(0));
+        let options = {
+            let progress = progress.clone();
+            DownloadOptions::new().progress(move |n| {
+                progress.fetch_add(n, Ordering::SeqCst);
+            })
+        };
+        client
+            .download_to_path(&child(&server, DATA_MD5), &path, &options)
+            .await
+            .expect("failed to download");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DATA);
+        assert_eq!(progress.load(Ordering::SeqCst), 5);
+        assert!(!path.with_added_extension("part").exists());
+
+        let bad_path = dir.join("bad.txt");
+        let error = client
+            .download_to_path(
+                &child(&server, "00000000000000000000000000000000"),
+                &bad_path,
+                &DownloadOptions::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Md5Mismatch { .. }), "{error:?}");
+        assert!(!bad_path.exists());
+        assert!(!bad_path.with_added_extension("part").exists());
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove temp dir");
+    }
+
+    #[tokio::test]
+    async fn download_to_path_resume() {
+        let (server, ranges) = range_file_server().await;
+        let client = test_client(&server);
+        let dir = temp_dir("download-to-path-resume");
+        let path = dir.join("file.txt");
+        let child = child(&server, DATA_MD5);
+
+        std::fs::write(path.with_added_extension("part"), "he").unwrap();
+        let progress = Arc::new(AtomicU64::new(0));
+        let options = {
+            let progress = progress.clone();
+            DownloadOptions::new().progress(move |n| {
+                progress.fetch_add(n, Ordering::SeqCst);
+            })
+        };
+        client
+            .download_to_path(&child, &path, &options)
+            .await
+            .expect("failed to download");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DATA);
+        assert_eq!(progress.load(Ordering::SeqCst), 5);
+        assert_eq!(*ranges.lock().unwrap(), [Some("bytes=2-".to_string())]);
+
+        // A complete part file only needs to be verified.
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(path.with_added_extension("part"), DATA).unwrap();
+        client
+            .download_to_path(&child, &path, &DownloadOptions::new())
+            .await
+            .expect("failed to download");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DATA);
+        assert_eq!(ranges.lock().unwrap().len(), 1);
+
+        // Corrupt data should be discarded.
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(path.with_added_extension("part"), "xx").unwrap();
+        let error = client
+            .download_to_path(&child, &path, &DownloadOptions::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Md5Mismatch { .. }), "{error:?}");
+        assert!(!path.with_added_extension("part").exists());
+
+        // Disabling resuming should start over.
+        std::fs::write(path.with_added_extension("part"), "xx").unwrap();
+        client
+            .download_to_path(&child, &path, &DownloadOptions::new().resume(false))
+            .await
+            .expect("failed to download");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DATA);
+        assert_eq!(ranges.lock().unwrap().last().unwrap(), &None);
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove temp dir");
+    }
+
+    #[tokio::test]
+    async fn download_to_path_resume_unsupported() {
+        let server = file_server().await;
+        let client = test_client(&server);
+        let dir = temp_dir("download-to-path-resume-unsupported");
+        let path = dir.join("file.txt");
+
+        std::fs::write(path.with_added_extension("part"), "xx").unwrap();
+        client
+            .download_to_path(&child(&server, DATA_MD5), &path, &DownloadOptions::new())
+            .await
+            .expect("failed to download");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DATA);
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove temp dir");
+    }
+
+    #[tokio::test]
+    async fn verify_file_checks_md5_and_size() {
+        let server = file_server().await;
+        let dir = temp_dir("verify-file");
+        let path = dir.join("file.txt");
+
+        std::fs::write(&path, DATA).unwrap();
+        verify_file(&child(&server, DATA_MD5), &path)
+            .await
+            .expect("failed to verify");
+
+        let error = verify_file(&child(&server, "00000000000000000000000000000000"), &path)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Md5Mismatch { .. }), "{error:?}");
+
+        std::fs::write(&path, "hello world").unwrap();
+        let error = verify_file(&child(&server, DATA_MD5), &path)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::SizeMismatch { .. }), "{error:?}");
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove temp dir");
+    }
+
+    #[test]
+    fn split_segments_covers_file() {
+        assert_eq!(split_segments(5, 2), [(0, 2), (3, 4)]);
+        assert_eq!(
+            split_segments(5, 5),
+            [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]
+        );
+        assert_eq!(split_segments(10, 4), [(0, 2), (3, 5), (6, 8), (9, 9)]);
+        assert_eq!(split_segments(4, 3), [(0, 1), (2, 3)]);
+    }
+
+    #[tokio::test]
+    async fn download_to_path_segmented() {
+        let (server, ranges) = range_file_server().await;
+        let client = test_client(&server);
+        let dir = temp_dir("download-to-path-segmented");
+        let path = dir.join("file.txt");
+
+        // Stale data in the part file should be overwritten.
+        std::fs::write(path.with_added_extension("part"), "xxxxxxxx").unwrap();
+        let progress = Arc::new(AtomicU64::new(0));
+        let options = {
+            let progress = progress.clone();
+            DownloadOptions::new().connections(2).progress(move |n| {
+                progress.fetch_add(n, Ordering::SeqCst);
+            })
+        };
+        client
+            .download_to_path(&child(&server, DATA_MD5), &path, &options)
+            .await
+            .expect("failed to download");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DATA);
+        assert_eq!(progress.load(Ordering::SeqCst), 5);
+        let mut ranges = ranges.lock().unwrap().clone();
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            [Some("bytes=0-2".to_string()), Some("bytes=3-4".to_string())]
+        );
+
+        let bad_path = dir.join("bad.txt");
+        let error = client
+            .download_to_path(
+                &child(&server, "00000000000000000000000000000000"),
+                &bad_path,
+                &options,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Md5Mismatch { .. }), "{error:?}");
+        assert!(!bad_path.with_added_extension("part").exists());
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove temp dir");
+    }
+
+    #[tokio::test]
+    async fn download_to_path_segmented_unsupported() {
+        let server = file_server().await;
+        let client = test_client(&server);
+        let dir = temp_dir("download-to-path-segmented-unsupported");
+        let path = dir.join("file.txt");
+
+        std::fs::write(path.with_added_extension("part"), "xxxxxxxx").unwrap();
+        client
+            .download_to_path(
+                &child(&server, DATA_MD5),
+                &path,
+                &DownloadOptions::new().connections(4),
+            )
+            .await
+            .expect("failed to download");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DATA);
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove temp dir");
+    }
+
+    #[tokio::test]
+    async fn download_to_path_segmented_later_range_ignored() {
+        // Only the first range is honored.
+        let server = TestServer::new(|request| match request.header("range") {
+            Some("bytes=0-1") => {
+                Response::new(206, &DATA.as_bytes()[..2]).header("Content-Range", "bytes 0-1/5")
+            }
+            _ => Response::new(200, DATA),
+        })
+        .await;
+        let client = test_client(&server);
+        let dir = temp_dir("download-to-path-segmented-later-range-ignored");
+        let path = dir.join("file.txt");
+
+        client
+            .download_to_path(
+                &child(&server, DATA_MD5),
+                &path,
+                &DownloadOptions::new().connections(3),
+            )
+            .await
+            .expect("failed to download");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DATA);
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove temp dir");
+    }
+}
@@ -0,0 +1,325 @@
+use super::Client;
+use crate::Error;
+use crate::Page;
+use crate::PageChild;
+use crate::PageChildKind;
+use crate::PageQuery;
+use futures_util::Stream;
+use futures_util::StreamExt;
+use futures_util::TryStreamExt;
+use futures_util::future;
+use futures_util::stream;
+use futures_util::stream::BoxStream;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+type DescendFn = dyn Fn(&WalkEntry) -> bool + Send + Sync;
+
+/// Options for walking a folder tree.
+#[derive(Clone)]
+pub struct WalkOptions {
+    /// The maximum depth of folders to descend into.
+    ///
+    /// A depth of 0 only lists the children of the root folder.
+    /// If `None`, there is no limit.
+    pub max_depth: Option<usize>,
+
+    /// The maximum number of folder listings to request at once.
+    ///
+    /// A value of 0 is treated as 1.
+    pub concurrency: usize,
+
+    /// The options used to list each folder.
+    pub query: PageQuery,
+
+    /// A function that decides whether to descend into a folder.
+    ///
+    /// Folders that are not descended into are still yielded, but their children are not listed.
+    /// If `None`, every folder is descended into.
+    pub descend: Option<Arc<DescendFn>>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            concurrency: 4,
+            query: PageQuery::default(),
+            descend: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for WalkOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalkOptions")
+            .field("max_depth", &self.max_depth)
+            .field("concurrency", &self.concurrency)
+            .field("query", &self.query)
+            .field("descend", &self.descend.is_some())
+            .finish()
+    }
+}
+
+/// An entry found while walking a folder tree.
+#[derive(Debug)]
+pub struct WalkEntry {
+    /// The names of the folders leading to this entry, starting below the root folder.
+    ///
+    /// This is empty for children of the root folder.
+    pub path: Vec<String>,
+
+    /// The number of folders between the root folder and this entry.
+    pub depth: usize,
+
+    /// The id of the folder containing this entry.
+    pub parent_id: String,
+
+    /// Whether the walk lists the children of this entry.
+    ///
+    /// This is false for files, and for folders that are not entered
+    /// because of the max depth, the descend filter, or because they were already visited.
+    pub descended: bool,
+
+    /// The entry
+    pub child: PageChild,
+}
+
+struct WalkContext {
+    client: Client,
+    options: WalkOptions,
+    semaphore: Semaphore,
+    visited: std::sync::Mutex<HashSet<String>>,
+}
+
+impl WalkContext {
+    async fn get_page(&self, code: &str) -> Result<Page, Error> {
+        let _permit = self.semaphore.acquire().await.expect("sem closed");
+        self.client.get_page_with(code, &self.options.query).await
+    }
+
+    /// Mark a folder as visited, returning false if it was already visited.
+    fn visit(&self, id: &str) -> bool {
+        self.visited
+            .lock()
+            .expect("visited poisoned")
+            .insert(id.to_string())
+    }
+}
+
+impl Client {
+    /// Walk a folder tree depth-first, yielding every file and folder below it.
+    pub fn walk(
+        &self,
+        code: &str,
+    ) -> impl Stream<Item = Result<WalkEntry, Error>> + Send + 'static {
+        self.walk_with(code, WalkOptions::default())
+    }
+
+    /// Walk a folder tree depth-first using the given options.
+    ///
+    /// Folders are only entered once, even if they are reachable from multiple places.
+    /// Errors listing subfolders are yielded in place of their children,
+    /// and do not end the walk.
+    pub fn walk_with(
+        &self,
+        code: &str,
+        options: WalkOptions,
+    ) -> impl Stream<Item = Result<WalkEntry, Error>> + Send + 'static {
+        let concurrency = options.concurrency.max(1);
+        let ctx = Arc::new(WalkContext {
+            client: self.clone(),
+            options,
+            semaphore: Semaphore::new(concurrency),
+            visited: std::sync::Mutex::new(HashSet::new()),
+        });
+        let code = code.to_string();
+
+        stream::once(async move {
+            let page = ctx.get_page(&code).await?;
+            ctx.visit(&page.id);
+            Ok::<_, Error>(walk_page(ctx, page, Vec::new(), 0))
+        })
+        .try_flatten()
+    }
+}
+
+fn walk_page(
+    ctx: Arc<WalkContext>,
+    page: Page,
+    path: Vec<String>,
+    depth: usize,
+) -> BoxStream<'static, Result<WalkEntry, Error>> {
+    let concurrency = ctx.options.concurrency.max(1);
+    let parent_id = page.id;
+
+    stream::iter(page.children.into_values())
+        .map(move |child| {
+            let ctx = ctx.clone();
+            let path = path.clone();
+            let parent_id = parent_id.clone();
+            async move {
+                let mut entry = WalkEntry {
+                    path,
+                    depth,
+                    parent_id,
+                    descended: false,
+                    child,
+                };
+                let can_descend = ctx
+                    .options
+                    .max_depth
+                    .is_none_or(|max_depth| depth < max_depth)
+                    && ctx
+                        .options
+                        .descend
+                        .as_ref()
+                        .is_none_or(|descend| descend(&entry));
+                let code = match (entry.child.kind, entry.child.code.as_ref()) {
+                    (PageChildKind::Folder, Some(code))
+                        if can_descend && ctx.visit(&entry.child.id) =>
+                    {
+                        Some(code.clone())
+                    }
+                    _ => None,
+                };
+                entry.descended = code.is_some();
+                let children = match code {
+                    Some(code) => match ctx.get_page(&code).await {
+                        Ok(page) => {
+                            let mut child_path = entry.path.clone();
+                            child_path.push(entry.child.name.clone());
+                            walk_page(ctx, page, child_path, depth + 1)
+                        }
+                        Err(error) => stream::once(future::ready(Err(error))).boxed(),
+                    },
+                    None => stream::empty().boxed(),
+                };
+
+                stream::once(future::ready(Ok(entry))).chain(children)
+            }
+        })
+        .buffered(concurrency)
+        .flatten()
+        .boxed()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::Response;
+    use crate::test_util::SCRIPT;
+    use crate::test_util::TestServer;
+    use crate::test_util::file_json;
+    use crate::test_util::folder_json;
+    use crate::test_util::page_json;
+    use crate::test_util::test_client;
+
+    /// Serve a tree where "sub" links back to the root folder.
+    async fn tree_server() -> TestServer {
+        TestServer::new(|request| {
+            let path = request.path.split('?').next().unwrap();
+            let json = match path {
+                "/dist/js/wt.obf.js" => return Response::new(200, SCRIPT),
+                "/contents/root" => page_json(
+                    "root",
+                    "root-id",
+                    2,
+                    &[
+                        file_json("a-id", "a.txt"),
+                        folder_json("sub-id", "sub", "sub"),
+                    ],
+                ),
+                "/contents/sub" => page_json(
+                    "sub",
+                    "sub-id",
+                    2,
+                    &[
+                        file_json("b-id", "b.txt"),
+                        folder_json("root-id", "loop", "root"),
+                    ],
+                ),
+                _ => return Response::new(404, ""),
+            };
+            Response::json(json)
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn walk_tree() {
+        let server = tree_server().await;
+        let client = test_client(&server);
+
+        let entries: Vec<WalkEntry> = client
+            .walk("root")
+            .try_collect()
+            .await
+            .expect("failed to walk");
+        let paths: Vec<(String, usize, &str)> = entries
+            .iter()
+            .map(|entry| {
+                let mut path = entry.path.clone();
+                path.push(entry.child.name.clone());
+                (path.join("/"), entry.depth, entry.parent_id.as_str())
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                ("a.txt".to_string(), 0, "root-id"),
+                ("sub".to_string(), 0, "root-id"),
+                ("sub/b.txt".to_string(), 1, "sub-id"),
+                ("sub/loop".to_string(), 1, "sub-id"),
+            ]
+        );
+
+        // "sub/loop" links back to the root folder, which was already visited.
+        let descended: Vec<bool> = entries.iter().map(|entry| entry.descended).collect();
+        assert_eq!(descended, [false, true, false, false]);
+    }
+
+    #[tokio::test]
+    async fn walk_max_depth() {
+        let server = tree_server().await;
+        let client = test_client(&server);
+
+        let options = WalkOptions {
+            max_depth: Some(0),
+            ..WalkOptions::default()
+        };
+        let entries: Vec<WalkEntry> = client
+            .walk_with("root", options)
+            .try_collect()
+            .await
+            .expect("failed to walk");
+        let names: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.child.name.as_str())
+            .collect();
+        assert_eq!(names, ["a.txt", "sub"]);
+        assert!(!entries[1].descended);
+    }
+
+    #[tokio::test]
+    async fn walk_descend_filter() {
+        let server = tree_server().await;
+        let client = test_client(&server);
+
+        let options = WalkOptions {
+            descend: Some(Arc::new(|entry: &WalkEntry| entry.child.name != "sub")),
+            ..WalkOptions::default()
+        };
+        let entries: Vec<WalkEntry> = client
+            .walk_with("root", options)
+            .try_collect()
+            .await
+            .expect("failed to walk");
+        let names: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.child.name.as_str())
+            .collect();
+        assert_eq!(names, ["a.txt", "sub"]);
+    }
+}
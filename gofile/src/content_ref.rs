@@ -0,0 +1,242 @@
+use std::str::FromStr;
+use url::Url;
+
+/// An error parsing a [`ContentRef`].
+#[derive(Debug, thiserror::Error)]
+pub enum ParseContentRefError {
+    /// The input was empty
+    #[error("empty link")]
+    Empty,
+
+    /// The input looked like a url, but could not be parsed as one
+    #[error("invalid url")]
+    InvalidUrl(#[from] url::ParseError),
+
+    /// The url did not use http or https
+    #[error("unsupported url scheme \"{0}\", expected \"https\"")]
+    UnsupportedScheme(String),
+
+    /// The url did not point to gofile.io
+    #[error("\"{0}\" is not a gofile.io host")]
+    UnsupportedHost(String),
+
+    /// The url pointed to gofile.io, but not to a folder or file
+    #[error(
+        "unsupported gofile.io path \"{0}\", expected \"/d/<code>\" or \"/download/<id>/<name>\""
+    )]
+    UnsupportedPath(String),
+
+    /// A folder code or file id contained invalid characters
+    #[error("\"{0}\" is not a valid code or id")]
+    InvalidCode(String),
+}
+
+/// A reference to content on gofile.io, parsed from a link or a bare folder code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContentRef {
+    /// A folder, by its code or id.
+    ///
+    /// This comes from links like `https://gofile.io/d/{code}`, or bare codes.
+    Folder {
+        /// The folder code or id
+        code: String,
+    },
+
+    /// A file, by its id.
+    ///
+    /// This comes from download links like `https://store1.gofile.io/download/web/{id}/{name}`.
+    /// These links do not say which folder the file is in.
+    File {
+        /// The file id
+        id: String,
+    },
+}
+
+impl ContentRef {
+    /// Parse a link or a bare folder code.
+    ///
+    /// Links may leave out the scheme, use the "www" subdomain, and have trailing slashes,
+    /// query strings, and fragments.
+    pub fn parse(input: &str) -> Result<Self, ParseContentRefError> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(ParseContentRefError::Empty);
+        }
+
+        if !input.contains('/') && !input.contains('.') {
+            let code = validate_code(input)?;
+            return Ok(Self::Folder { code });
+        }
+
+        let url = if input.contains("://") {
+            Url::parse(input)?
+        } else {
+            Url::parse(&format!("https://{input}"))?
+        };
+
+        Self::from_url(&url)
+    }
+
+    /// Parse a link.
+    pub fn from_url(url: &Url) -> Result<Self, ParseContentRefError> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ParseContentRefError::UnsupportedScheme(
+                url.scheme().to_string(),
+            ));
+        }
+
+        let host = url.host_str().unwrap_or_default();
+        let is_gofile_host = host.eq_ignore_ascii_case("gofile.io")
+            || host
+                .get(host.len().saturating_sub(".gofile.io".len())..)
+                .is_some_and(|suffix| suffix.eq_ignore_ascii_case(".gofile.io"));
+        if !is_gofile_host {
+            return Err(ParseContentRefError::UnsupportedHost(host.to_string()));
+        }
+
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+            .unwrap_or_default();
+        match segments.as_slice() {
+            ["d", code] => Ok(Self::Folder {
+                code: validate_code(code)?,
+            }),
+            ["download", "web", id, ..] | ["download", id, ..] => Ok(Self::File {
+                id: validate_code(id)?,
+            }),
+            _ => Err(ParseContentRefError::UnsupportedPath(
+                url.path().to_string(),
+            )),
+        }
+    }
+}
+
+impl FromStr for ContentRef {
+    type Err = ParseContentRefError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+/// Check that a code or id only has characters that can appear in one.
+fn validate_code(code: &str) -> Result<String, ParseContentRefError> {
+    let is_valid = code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !is_valid {
+        return Err(ParseContentRefError::InvalidCode(code.to_string()));
+    }
+
+    Ok(code.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn folder(code: &str) -> ContentRef {
+        ContentRef::Folder { code: code.into() }
+    }
+
+    #[test]
+    fn parse_folders() {
+        for input in [
+            "abc123",
+            "  abc123\n",
+            "https://gofile.io/d/abc123",
+            "http://gofile.io/d/abc123",
+            "https://www.gofile.io/d/abc123",
+            "https://gofile.io/d/abc123/",
+            "https://gofile.io/d/abc123?password=1#top",
+            "gofile.io/d/abc123",
+            "www.gofile.io/d/abc123",
+            "HTTPS://GOFILE.IO/d/abc123",
+        ] {
+            assert_eq!(
+                ContentRef::parse(input).unwrap(),
+                folder("abc123"),
+                "{input}"
+            );
+        }
+
+        let id = "4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f";
+        assert_eq!(ContentRef::parse(id).unwrap(), folder(id));
+    }
+
+    #[test]
+    fn parse_files() {
+        let file = ContentRef::File {
+            id: "4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f".into(),
+        };
+        for input in [
+            "https://store1.gofile.io/download/web/4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f/file.txt",
+            "https://store-eu-par-1.gofile.io/download/4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f/file.txt",
+            "https://gofile.io/download/web/4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f",
+        ] {
+            assert_eq!(ContentRef::parse(input).unwrap(), file, "{input}");
+        }
+    }
+
+    #[test]
+    fn page_child_from_download_link() {
+        let child = crate::PageChild::from_download_link(
+            "store1.gofile.io/download/web/4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f/my%20file.txt",
+        )
+        .unwrap();
+        assert_eq!(child.id, "4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f");
+        assert_eq!(child.name, "my file.txt");
+        assert_eq!(
+            child.link.as_deref(),
+            Some(
+                "https://store1.gofile.io/download/web/4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f/my%20file.txt"
+            )
+        );
+        assert_eq!(child.kind, crate::PageChildKind::File);
+
+        let child = crate::PageChild::from_download_link(
+            "https://gofile.io/download/4d5a2c1e-1b2a-4c3d-9e8f-0a1b2c3d4e5f",
+        )
+        .unwrap();
+        assert_eq!(child.name, child.id);
+
+        assert!(crate::PageChild::from_download_link("https://gofile.io/d/abc123").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            ContentRef::parse("  "),
+            Err(ParseContentRefError::Empty)
+        ));
+        assert!(matches!(
+            ContentRef::parse("https://"),
+            Err(ParseContentRefError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            ContentRef::parse("ftp://gofile.io/d/abc"),
+            Err(ParseContentRefError::UnsupportedScheme(scheme)) if scheme == "ftp"
+        ));
+        assert!(matches!(
+            ContentRef::parse("https://example.com/d/abc"),
+            Err(ParseContentRefError::UnsupportedHost(host)) if host == "example.com"
+        ));
+        assert!(matches!(
+            ContentRef::parse("https://notgofile.io/d/abc"),
+            Err(ParseContentRefError::UnsupportedHost(_))
+        ));
+        assert!(matches!(
+            ContentRef::parse("https://gofile.io/myfiles"),
+            Err(ParseContentRefError::UnsupportedPath(path)) if path == "/myfiles"
+        ));
+        assert!(matches!(
+            ContentRef::parse("https://gofile.io/d/abc/extra"),
+            Err(ParseContentRefError::UnsupportedPath(_))
+        ));
+        assert!(matches!(
+            ContentRef::parse("abc$123"),
+            Err(ParseContentRefError::InvalidCode(code)) if code == "abc$123"
+        ));
+    }
+}
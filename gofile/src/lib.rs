@@ -1,12 +1,40 @@
 mod client;
+mod content_ref;
 mod model;
+mod retry;
+#[cfg(test)]
+mod test_util;
+mod upload_part;
+mod website_token;
 
 pub use self::client::Client;
+pub use self::client::ClientBuilder;
+pub use self::client::Download;
+pub use self::client::DownloadOptions;
+pub use self::client::WalkEntry;
+pub use self::client::WalkOptions;
+pub use self::client::verify_file;
+pub use self::content_ref::ContentRef;
+pub use self::content_ref::ParseContentRefError;
 pub(crate) use self::model::AccountResponse;
 pub(crate) use self::model::ApiResponse;
+pub use self::model::FolderInfo;
 pub use self::model::Page;
 pub use self::model::PageChild;
+pub use self::model::PageChildKind;
+pub use self::model::PageQuery;
+pub use self::model::SortDirection;
+pub use self::model::SortField;
 pub use self::model::UploadInfo;
+pub use self::model::UploadOptions;
+pub use self::retry::RetryPolicy;
+pub use self::upload_part::reader_part;
+pub use self::upload_part::stream_part;
+pub use self::website_token::FixedWebsiteTokenProvider;
+pub use self::website_token::NativeWebsiteTokenProvider;
+pub use self::website_token::RemoteScriptWebsiteTokenProvider;
+pub use self::website_token::ScriptFileWebsiteTokenProvider;
+pub use self::website_token::WebsiteTokenProvider;
 pub use reqwest::multipart::Part as MultipartPart;
 
 /// Library error type
@@ -27,6 +55,96 @@ pub enum Error {
     /// rquickjs error
     #[error("js error")]
     JsError(#[from] rquickjs::Error),
+
+    /// Io error
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+
+    /// Json error
+    #[error("json error")]
+    Json(#[from] serde_json::Error),
+
+    /// The folder is protected by a password, and none was given
+    #[error("password required")]
+    PasswordRequired,
+
+    /// The folder is protected by a password, and the given one was wrong
+    #[error("wrong password")]
+    WrongPassword,
+
+    /// The page child is not a file, so it cannot be downloaded
+    #[error("not a file")]
+    NotAFile,
+
+    /// The md5 hash of a download did not match the expected hash
+    #[error("md5 hash mismatch, expected \"{expected}\" but got \"{actual}\"")]
+    Md5Mismatch {
+        /// The expected hash, as a hex string
+        expected: String,
+
+        /// The actual hash, as a hex string
+        actual: String,
+    },
+
+    /// The size of a download did not match the expected size
+    #[error("size mismatch, expected {expected} bytes but got {actual} bytes")]
+    SizeMismatch {
+        /// The expected size, in bytes
+        expected: u64,
+
+        /// The actual size, in bytes
+        actual: u64,
+    },
+
+    /// The content does not exist
+    #[error("content not found")]
+    NotFound,
+
+    /// The action needs a premium account
+    #[error("premium account required")]
+    NotPremium,
+
+    /// The token is not allowed to perform the action
+    #[error("forbidden")]
+    Forbidden,
+
+    /// Too many requests were made, and the server asked us to try later
+    #[error("rate limited")]
+    RateLimited,
+
+    /// The api returned an unknown error status
+    #[error("api error \"{status}\"")]
+    Api {
+        /// The raw status
+        status: String,
+    },
+}
+
+impl Error {
+    /// Make an error from a non-"ok" api status.
+    pub(crate) fn from_api_status(status: String) -> Self {
+        match status.as_str() {
+            "error-notFound" => Self::NotFound,
+            "error-passwordRequired" => Self::PasswordRequired,
+            "error-passwordWrong" => Self::WrongPassword,
+            "error-notPremium" => Self::NotPremium,
+            "error-forbidden" | "error-auth" | "error-wrongToken" => Self::Forbidden,
+            "error-rateLimit" | "error-tooManyRequests" => Self::RateLimited,
+            _ => Self::Api { status },
+        }
+    }
+
+    /// Check if this error may have been caused by the server rejecting the website token.
+    pub(crate) fn is_rejected_website_token(&self) -> bool {
+        match self {
+            Self::Forbidden => true,
+            Self::Reqwest(error) => matches!(
+                error.status(),
+                Some(reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN)
+            ),
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
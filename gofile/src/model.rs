@@ -1,16 +1,31 @@
 mod account_response;
+mod folder_info;
 mod page;
+mod page_query;
 mod upload_info;
+mod upload_options;
 
 pub(crate) use self::account_response::AccountResponse;
+pub use self::folder_info::FolderInfo;
 pub use self::page::Page;
 pub use self::page::PageChild;
+pub use self::page::PageChildKind;
+pub use self::page_query::PageQuery;
+pub use self::page_query::SortDirection;
+pub use self::page_query::SortField;
 pub use self::upload_info::UploadInfo;
+pub use self::upload_options::UploadOptions;
 
+/// An api response.
+///
+/// Use `serde_json::Value` as the data type to inspect the status before parsing the data.
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct ApiResponse<T> {
-    // /// The status
-    // pub status: String,
+    /// The status.
+    ///
+    /// This is "ok" on success.
+    pub status: String,
+
     /// The data
     pub data: T,
 }
@@ -0,0 +1,20 @@
+/// The info on a newly created folder
+#[derive(Debug, serde::Deserialize)]
+pub struct FolderInfo {
+    /// The id of the folder.
+    ///
+    /// This can be used as the parent of other folders and uploads.
+    pub id: String,
+
+    /// The folder code.
+    ///
+    /// This shows up in the url as `https://gofile.io/d/{code}`.
+    pub code: String,
+
+    /// The folder name
+    pub name: String,
+
+    /// The id of the parent folder
+    #[serde(rename = "parentFolder")]
+    pub parent_folder: String,
+}
@@ -1,11 +1,16 @@
-use std::collections::HashMap;
+use crate::ContentRef;
+use crate::ParseContentRefError;
+use indexmap::IndexMap;
 use time::OffsetDateTime;
 
+/// The type of a page child.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, serde::Deserialize, serde::Serialize)]
 pub enum PageChildKind {
+    /// A file
     #[serde(rename = "file")]
     File,
 
+    /// A folder
     #[serde(rename = "folder")]
     Folder,
 }
@@ -68,6 +73,55 @@ pub struct PageChild {
     pub children_count: Option<u64>,
 }
 
+impl PageChild {
+    /// Make a file child from a direct download link,
+    /// like `https://store1.gofile.io/download/web/{id}/{name}`.
+    ///
+    /// The name is taken from the link, or is the file id if the link has no name.
+    /// Links do not include the size, md5 hash, or times of the file,
+    /// so downloads of the child are not verified and its times are set to now.
+    pub fn from_download_link(link: &str) -> Result<Self, ParseContentRefError> {
+        let id = match ContentRef::parse(link)? {
+            ContentRef::File { id } => id,
+            ContentRef::Folder { .. } => {
+                return Err(ParseContentRefError::UnsupportedPath(link.to_string()));
+            }
+        };
+        let link = if link.contains("://") {
+            link.trim().to_string()
+        } else {
+            format!("https://{}", link.trim())
+        };
+        let url = url::Url::parse(&link)?;
+        let name = url
+            .path_segments()
+            .and_then(|segments| {
+                segments
+                    .skip_while(|segment| *segment != id)
+                    .nth(1)
+                    .filter(|segment| !segment.is_empty())
+            })
+            .map(|name| percent_encoding::percent_decode_str(name).decode_utf8_lossy())
+            .map(String::from)
+            .unwrap_or_else(|| id.clone());
+
+        let now = OffsetDateTime::now_utc();
+        Ok(Self {
+            download_count: None,
+            create_time: now,
+            mod_time: now,
+            link: Some(link),
+            md5: None,
+            size: None,
+            name,
+            kind: PageChildKind::File,
+            code: None,
+            id,
+            children_count: None,
+        })
+    }
+}
+
 /// A download page
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 pub struct Page {
@@ -88,7 +142,8 @@ pub struct Page {
     /// This uniquely identifies the folder on the entire website.
     pub id: String,
 
-    pub children: HashMap<String, PageChild>,
+    /// The children, in the order the server sorted them.
+    pub children: IndexMap<String, PageChild>,
     #[serde(rename = "totalSize")]
     pub total_size: u64,
     #[serde(rename = "modTime", with = "time::serde::timestamp")]
@@ -0,0 +1,91 @@
+/// The field to sort a folder listing by.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SortField {
+    /// Sort by name
+    #[default]
+    Name,
+
+    /// Sort by size
+    Size,
+
+    /// Sort by creation time
+    CreateTime,
+
+    /// Sort by modification time
+    ModTime,
+
+    /// Sort by download count
+    DownloadCount,
+}
+
+impl SortField {
+    /// Get the name the api uses for this field.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Size => "size",
+            Self::CreateTime => "createTime",
+            Self::ModTime => "modTime",
+            Self::DownloadCount => "downloadCount",
+        }
+    }
+}
+
+/// The direction to sort a folder listing in.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SortDirection {
+    /// Smallest first
+    #[default]
+    Ascending,
+
+    /// Largest first
+    Descending,
+}
+
+impl SortDirection {
+    /// Get the value the api uses for this direction.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ascending => "1",
+            Self::Descending => "-1",
+        }
+    }
+}
+
+/// Options for listing a folder.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PageQuery {
+    /// Only include children whose names match this text.
+    ///
+    /// An empty string does not filter anything.
+    pub content_filter: String,
+
+    /// The field to sort by
+    pub sort_field: SortField,
+
+    /// The direction to sort in
+    pub sort_direction: SortDirection,
+
+    /// The number of children to request at once.
+    ///
+    /// This does not limit the number of children returned,
+    /// it only controls how many requests are needed to fetch all of them.
+    pub page_size: u64,
+
+    /// The password of the folder, if it is protected by one.
+    ///
+    /// This is hashed before it is sent to the server.
+    pub password: Option<String>,
+}
+
+impl Default for PageQuery {
+    fn default() -> Self {
+        Self {
+            content_filter: String::new(),
+            sort_field: SortField::default(),
+            sort_direction: SortDirection::default(),
+            page_size: 1000,
+            password: None,
+        }
+    }
+}
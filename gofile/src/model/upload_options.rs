@@ -0,0 +1,9 @@
+/// Options for uploading a file.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct UploadOptions {
+    /// The id of the folder to upload into.
+    ///
+    /// The folder must belong to the account of the token used to upload.
+    /// If `None`, the server puts the file in a new folder.
+    pub folder_id: Option<String>,
+}
@@ -0,0 +1,336 @@
+use reqwest::Method;
+use reqwest::StatusCode;
+use reqwest::header::RETRY_AFTER;
+use std::hash::BuildHasher;
+use std::time::Duration;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc2822;
+
+/// A policy for retrying requests that failed for transient reasons.
+///
+/// Idempotent requests, like GET requests, are retried on connection errors, timeouts,
+/// 5xx status codes, and 429 status codes.
+/// Other requests, like logging in or creating folders, are only retried if they could not connect,
+/// since the server may have already acted on them.
+/// Uploads are never retried, since their bodies cannot be replayed.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of times to try a request, including the first try.
+    ///
+    /// A value of 0 or 1 disables retries.
+    pub max_attempts: u32,
+
+    /// The delay before the first retry.
+    ///
+    /// This is doubled for each following retry.
+    pub initial_backoff: Duration,
+
+    /// The maximum delay between retries.
+    ///
+    /// This does not limit delays requested by the server with a `Retry-After` header,
+    /// which are limited by `max_retry_after` instead.
+    pub max_backoff: Duration,
+
+    /// The longest delay requested by the server with a `Retry-After` header that is waited out.
+    ///
+    /// If the server asks for a longer delay, the request is not retried.
+    pub max_retry_after: Duration,
+
+    /// Whether to randomize delays.
+    ///
+    /// This picks a delay between half and all of the computed backoff,
+    /// which keeps many clients from retrying at the same moment.
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+    /// Make a policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Get the delay before the given retry, where 1 is the first retry.
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(retry.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        if !self.jitter {
+            return backoff;
+        }
+
+        // Avoid pulling in a rng crate just for this.
+        let random = std::collections::hash_map::RandomState::new().hash_one(retry);
+        let fraction = (random % 1_000) as f64 / 1_000.0;
+        backoff.mul_f64(0.5 + fraction / 2.0)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_retry_after: Duration::from_secs(60),
+            jitter: true,
+        }
+    }
+}
+
+/// Check if a response with the given status code should be retried.
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Check if a request with the given method can be sent again without changing its effect.
+pub(crate) fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// Check if a request that failed with the given error should be retried.
+///
+/// Requests that are not idempotent are only retried if they never reached the server.
+pub(crate) fn is_retryable_error(error: &reqwest::Error, is_idempotent: bool) -> bool {
+    error.is_connect() || (is_idempotent && (error.is_timeout() || error.is_request()))
+}
+
+/// Get the delay the server asked for with a `Retry-After` header.
+pub(crate) fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = OffsetDateTime::parse(value, &Rfc2822).ok()?;
+    let delay = date - OffsetDateTime::now_utc();
+    Some(delay.try_into().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Client;
+    use crate::Error;
+    use crate::FixedWebsiteTokenProvider;
+    use crate::test_util::Response;
+    use crate::test_util::TestServer;
+    use crate::test_util::page_json;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Instant;
+
+    const ACCOUNT: &str = r#"{"status":"ok","data":{"token":"guest-token"}}"#;
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(10),
+            max_retry_after: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+
+    /// Make a server that runs the given function for each attempt, numbered from 1.
+    async fn fault_server<F>(fault: F) -> (TestServer, Arc<AtomicUsize>)
+    where
+        F: Fn(usize) -> Response + Send + Sync + 'static,
+    {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let attempts = attempts.clone();
+            TestServer::new(move |_request| fault(attempts.fetch_add(1, Ordering::SeqCst) + 1))
+                .await
+        };
+        (server, attempts)
+    }
+
+    fn client(server: &TestServer, policy: RetryPolicy) -> Client {
+        Client::builder()
+            .api_base_url(server.url())
+            .upload_base_url(server.url())
+            .website_token_provider(FixedWebsiteTokenProvider::new("website-token"))
+            .retry_policy(policy)
+            .build()
+            .expect("failed to build client")
+    }
+
+    /// Make a response for a GET request of an empty page.
+    fn page() -> Response {
+        Response::json(page_json("abc123", "page-id", 0, &[]))
+    }
+
+    /// Make an idempotent request.
+    async fn get_page(client: &Client) -> Result<crate::Page, Error> {
+        client.set_token("guest-token".into());
+        client.get_page("abc123").await
+    }
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            max_retry_after: Duration::from_secs(5),
+            jitter: false,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+        assert_eq!(policy.backoff(100), Duration::from_secs(5));
+
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for retry in 1..10 {
+            let backoff = policy.backoff(retry);
+            assert!(backoff >= Duration::from_millis(500), "{backoff:?}");
+            assert!(backoff <= Duration::from_secs(5), "{backoff:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors() {
+        let (server, attempts) = fault_server(|attempt| match attempt {
+            1 => Response::new(500, ""),
+            2 => Response::new(503, ""),
+            _ => page(),
+        })
+        .await;
+        let client = client(&server, fast_policy());
+
+        get_page(&client).await.expect("failed to get page");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retries_connection_resets() {
+        let (server, attempts) = fault_server(|attempt| match attempt {
+            1 => Response::reset(),
+            _ => page(),
+        })
+        .await;
+        let client = client(&server, fast_policy());
+
+        get_page(&client).await.expect("failed to get page");
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_posts_the_server_may_have_acted_on() {
+        let (server, attempts) = fault_server(|attempt| match attempt {
+            1 => Response::new(503, ""),
+            _ => Response::json(ACCOUNT),
+        })
+        .await;
+        let client = client(&server, fast_policy());
+
+        client.login_guest().await.unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let (server, attempts) = fault_server(|attempt| match attempt {
+            1 => Response::reset(),
+            _ => Response::json(ACCOUNT),
+        })
+        .await;
+        let client = self::client(&server, fast_policy());
+
+        client.login_guest().await.unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let (server, attempts) = fault_server(|_attempt| Response::new(502, "")).await;
+        let client = client(&server, fast_policy());
+
+        let error = get_page(&client).await.unwrap_err();
+        assert!(
+            matches!(&error, Error::Reqwest(error) if error.status() == Some(StatusCode::BAD_GATEWAY)),
+            "{error:?}"
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let (server, attempts) = fault_server(|_attempt| Response::new(404, "")).await;
+        let client = client(&server, fast_policy());
+
+        get_page(&client).await.unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn honors_retry_after() {
+        let (server, attempts) = fault_server(|attempt| match attempt {
+            1 => Response::new(429, "").header("Retry-After", "1"),
+            _ => page(),
+        })
+        .await;
+        let client = client(&server, fast_policy());
+
+        let start = Instant::now();
+        get_page(&client).await.expect("failed to get page");
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_long_retry_after() {
+        let (server, attempts) = fault_server(|attempt| match attempt {
+            1 => Response::new(429, "").header("Retry-After", "86400"),
+            _ => page(),
+        })
+        .await;
+        let client = client(&server, fast_policy());
+
+        let start = Instant::now();
+        let error = get_page(&client).await.unwrap_err();
+        assert!(matches!(error, Error::RateLimited), "{error:?}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_uploads() {
+        let (server, attempts) = fault_server(|_attempt| Response::new(503, "")).await;
+        let client = client(&server, fast_policy());
+        client.set_token("guest-token".into());
+
+        let stream = futures_util::stream::iter([Ok::<_, std::io::Error>("data")]);
+        let part = crate::MultipartPart::stream(reqwest::Body::wrap_stream(stream));
+        client.upload(part).await.unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn disabled() {
+        let (server, attempts) = fault_server(|_attempt| Response::new(500, "")).await;
+        let client = client(&server, RetryPolicy::none());
+
+        client.login_guest().await.unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn rfc2822_parses_http_dates() {
+        // Retry-After dates are http dates, which are assumed to be a subset of rfc 2822.
+        let date = OffsetDateTime::parse("Wed, 21 Oct 2015 07:28:00 GMT", &Rfc2822);
+        assert!(date.is_ok(), "{date:?}");
+    }
+}
@@ -0,0 +1,320 @@
+use crate::Client;
+use crate::RetryPolicy;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+
+/// A website token script that echoes its inputs.
+pub(crate) const SCRIPT: &str = r#"function generateWT(token) {
+    return navigator.userAgent + "|" + navigator.language + "|" + token;
+}"#;
+
+/// Make a json entry for a file child.
+pub(crate) fn file_json(id: &str, name: &str) -> String {
+    format!(
+        r#""{id}": {{
+            "createTime": 1700000000,
+            "modTime": 1700000001,
+            "name": "{name}",
+            "type": "file",
+            "id": "{id}"
+        }}"#
+    )
+}
+
+/// Make a json entry for a folder child.
+pub(crate) fn folder_json(id: &str, name: &str, code: &str) -> String {
+    format!(
+        r#""{id}": {{
+            "createTime": 1700000000,
+            "modTime": 1700000001,
+            "name": "{name}",
+            "type": "folder",
+            "code": "{code}",
+            "id": "{id}",
+            "childrenCount": 0
+        }}"#
+    )
+}
+
+/// Make a page api response.
+pub(crate) fn page_json(code: &str, id: &str, children_count: u64, children: &[String]) -> String {
+    format!(
+        r#"{{
+            "status": "ok",
+            "data": {{
+                "childrenCount": {children_count},
+                "code": "{code}",
+                "createTime": 1700000000,
+                "id": "{id}",
+                "children": {{ {} }},
+                "totalSize": 0,
+                "modTime": 1700000001,
+                "name": "folder",
+                "public": true,
+                "totalDownloadCount": 0
+            }}
+        }}"#,
+        children.join(",")
+    )
+}
+
+/// Make an upload api response.
+pub(crate) fn upload_json(name: &str, parent_folder: &str, parent_folder_code: &str) -> String {
+    format!(
+        r#"{{
+            "status": "ok",
+            "data": {{
+                "createTime": 1700000000,
+                "downloadPage": "https://gofile.io/d/{parent_folder_code}",
+                "id": "{name}-id",
+                "md5": "5d41402abc4b2a76b9719d911017c592",
+                "mimetype": "text/plain",
+                "modTime": 1700000001,
+                "name": "{name}",
+                "parentFolder": "{parent_folder}",
+                "parentFolderCode": "{parent_folder_code}",
+                "servers": ["store1"],
+                "size": 5,
+                "type": "file"
+            }}
+        }}"#
+    )
+}
+
+/// Make a client that talks to a test server, with a token already set.
+///
+/// Retries are disabled, so that tests of error handling see every failure.
+pub(crate) fn test_client(server: &TestServer) -> Client {
+    let client = Client::builder()
+        .retry_policy(RetryPolicy::none())
+        .api_base_url(server.url())
+        .website_base_url(server.url())
+        .upload_base_url(server.url())
+        .build()
+        .expect("failed to build client");
+    client.set_token("guest-token".into());
+    client
+}
+
+/// A request received by the test server.
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    /// The request method
+    pub method: String,
+
+    /// The request path, including the query string
+    pub path: String,
+
+    /// The request headers, with lowercase names
+    pub headers: Vec<(String, String)>,
+
+    /// The request body
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Get the value of a header by its lowercase name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A response for the test server to send.
+#[derive(Debug, Clone)]
+pub(crate) struct Response {
+    /// The status code
+    pub status: u16,
+
+    /// Extra response headers
+    pub headers: Vec<(String, String)>,
+
+    /// The response body
+    pub body: Vec<u8>,
+
+    /// If true, close the connection without responding.
+    pub reset: bool,
+}
+
+impl Response {
+    /// Make a response with the given status and body.
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            reset: false,
+        }
+    }
+
+    /// Make a response that closes the connection without responding.
+    pub fn reset() -> Self {
+        Self {
+            reset: true,
+            ..Self::new(0, "")
+        }
+    }
+
+    /// Make a 200 response with a json body.
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::new(200, body.into()).header("Content-Type", "application/json")
+    }
+
+    /// Set the status code.
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+type Handler = dyn Fn(Request) -> Response + Send + Sync;
+
+/// A tiny HTTP/1.1 server for tests.
+///
+/// Every connection serves exactly one request.
+pub(crate) struct TestServer {
+    addr: SocketAddr,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl TestServer {
+    /// Start a server, using the given function to answer requests.
+    pub async fn new<F>(handler: F) -> Self
+    where
+        F: Fn(Request) -> Response + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind test server");
+        let addr = listener.local_addr().expect("missing local addr");
+        let handler: Arc<Handler> = Arc::new(handler);
+        let handle = tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let _ = handle_connection(stream, &*handler).await;
+                });
+            }
+        });
+
+        Self { addr, handle }
+    }
+
+    /// Get the base url of this server, without a trailing slash.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, handler: &Handler) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let header_end = loop {
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        let mut chunk = [0; 4096];
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).into_owned();
+    let mut body = buffer[header_end + 4..].to_vec();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    if let Some(len) = request.header("content-length") {
+        let len: usize = len.parse().expect("invalid content-length");
+        while body.len() < len {
+            let mut chunk = [0; 4096];
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..n]);
+        }
+    } else if request.header("transfer-encoding") == Some("chunked") {
+        while !body.ends_with(b"0\r\n\r\n") {
+            let mut chunk = [0; 4096];
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..n]);
+        }
+        body = decode_chunked(&body);
+    }
+    request.body = body;
+
+    let response = handler(request);
+    if response.reset {
+        return Ok(());
+    }
+
+    let mut head = format!(
+        "HTTP/1.1 {} Test\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in response.headers.iter() {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+fn decode_chunked(mut input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    loop {
+        let Some(line_end) = input.windows(2).position(|window| window == b"\r\n") else {
+            return output;
+        };
+        let size = std::str::from_utf8(&input[..line_end]).unwrap_or("0");
+        let size = usize::from_str_radix(size.trim(), 16).unwrap_or(0);
+        if size == 0 {
+            return output;
+        }
+        let start = line_end + 2;
+        output.extend_from_slice(&input[start..start + size]);
+        input = &input[start + size + 2..];
+    }
+}
@@ -0,0 +1,84 @@
+use crate::MultipartPart;
+use bytes::Bytes;
+use futures_util::TryStream;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+/// Make a multipart part that uploads the contents of a stream.
+///
+/// If the length is known, it is sent up front.
+/// Otherwise, the upload is sent with chunked transfer encoding.
+/// Like all uploads, uploads of these parts are never retried. See [`RetryPolicy`](crate::RetryPolicy).
+pub fn stream_part<S>(stream: S, len: Option<u64>) -> MultipartPart
+where
+    S: TryStream + Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    Bytes: From<S::Ok>,
+{
+    let body = reqwest::Body::wrap_stream(stream);
+    match len {
+        Some(len) => MultipartPart::stream_with_length(body, len),
+        None => MultipartPart::stream(body),
+    }
+}
+
+/// Make a multipart part that uploads everything read from a reader.
+///
+/// See [`stream_part`].
+pub fn reader_part<R>(reader: R, len: Option<u64>) -> MultipartPart
+where
+    R: AsyncRead + Send + 'static,
+{
+    stream_part(ReaderStream::new(reader), len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::Response;
+    use crate::test_util::TestServer;
+    use crate::test_util::test_client;
+    use crate::test_util::upload_json;
+
+    /// Find the contents of the "file" field in a multipart body.
+    fn file_field(body: &[u8]) -> String {
+        let body = String::from_utf8_lossy(body);
+        let start = body.find("\r\n\r\n").expect("missing part body") + 4;
+        let end = body[start..].find("\r\n--").expect("missing boundary") + start;
+        body[start..end].to_string()
+    }
+
+    #[tokio::test]
+    async fn upload_reader_with_unknown_length() {
+        let server = TestServer::new(|request| {
+            assert_eq!(request.header("transfer-encoding"), Some("chunked"));
+            assert_eq!(file_field(&request.body), "streamed data");
+            Response::json(upload_json("backup.tar", "new-id", "new-code"))
+        })
+        .await;
+        let client = test_client(&server);
+
+        let reader = std::io::Cursor::new(b"streamed data".to_vec());
+        let part = reader_part(reader, None).file_name("backup.tar");
+        let upload_info = client.upload(part).await.expect("failed to upload");
+        assert_eq!(upload_info.name, "backup.tar");
+    }
+
+    #[tokio::test]
+    async fn upload_stream_with_length() {
+        let server = TestServer::new(|request| {
+            assert!(request.header("content-length").is_some());
+            assert_eq!(file_field(&request.body), "hello world");
+            Response::json(upload_json("file.txt", "new-id", "new-code"))
+        })
+        .await;
+        let client = test_client(&server);
+
+        let stream = futures_util::stream::iter([
+            Ok::<_, std::io::Error>("hello "),
+            Ok::<_, std::io::Error>("world"),
+        ]);
+        let part = stream_part(stream, Some(11)).file_name("file.txt");
+        client.upload(part).await.expect("failed to upload");
+    }
+}
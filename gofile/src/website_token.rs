@@ -0,0 +1,251 @@
+use crate::Client;
+use crate::Error;
+use futures_util::FutureExt;
+use futures_util::future::BoxFuture;
+use reqwest::Method;
+use sha2::Digest;
+use sha2::Sha256;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// The length of the time window a website token is valid for, in seconds.
+const WINDOW_SECS: u32 = 60 * 60 * 4;
+
+fn unix_epoch_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the unix epoch")
+        .as_secs_f64()
+}
+
+/// Get the time window website tokens are currently generated for.
+pub(crate) fn current_window() -> u64 {
+    (unix_epoch_secs() / f64::from(WINDOW_SECS)) as u64
+}
+
+/// See: https://github.com/yt-dlp/yt-dlp/issues/16117#issuecomment-4011609858
+fn generate_website_token(user_agent: &str, language: &str, token: &str, time_secs: f64) -> String {
+    let time_4 = (time_secs / f64::from(WINDOW_SECS)) as u64;
+    let hash_salt = "gf2026x";
+
+    let data = format!("{user_agent}::{language}::{token}::{time_4}::{hash_salt}");
+
+    let mut hasher = Sha256::new();
+    hasher.update(data.as_bytes());
+    let hash = hasher.finalize();
+
+    base16ct::lower::encode_string(&hash)
+}
+
+/// Run a website token script, calling its `generateWT` function.
+///
+/// This blocks, so it should be run with `spawn_blocking`.
+fn eval_script(
+    script: String,
+    user_agent: String,
+    language: String,
+    token: String,
+) -> Result<String, Error> {
+    let runtime = rquickjs::Runtime::new()?;
+    let ctx = rquickjs::Context::full(&runtime)?;
+
+    ctx.with(|ctx| {
+        let globals = ctx.globals();
+        let navigator = rquickjs::Object::new(ctx.clone())?;
+        navigator.set("userAgent", user_agent)?;
+        navigator.set("language", language)?;
+
+        globals.set("navigator", navigator)?;
+
+        let result: Result<(), _> = ctx.eval(script);
+        result?;
+
+        let generate_wt: rquickjs::Function = globals.get("generateWT")?;
+        let website_token: String = generate_wt.call((token,))?;
+
+        Ok(website_token)
+    })
+}
+
+/// A way to generate website tokens.
+///
+/// Website tokens are sent along with api requests,
+/// and are derived from the account token, user agent, language, and current time.
+pub trait WebsiteTokenProvider: std::fmt::Debug + Send + Sync {
+    /// Generate a website token for the given account token.
+    ///
+    /// The user agent and language to generate the token for can be taken from the client.
+    fn generate<'a>(
+        &'a self,
+        client: &'a Client,
+        token: &'a str,
+    ) -> BoxFuture<'a, Result<String, Error>>;
+}
+
+/// Generate website tokens with a native reimplementation of the website's algorithm.
+///
+/// This needs no network access, but may break if the website changes its algorithm.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NativeWebsiteTokenProvider;
+
+impl WebsiteTokenProvider for NativeWebsiteTokenProvider {
+    fn generate<'a>(
+        &'a self,
+        client: &'a Client,
+        token: &'a str,
+    ) -> BoxFuture<'a, Result<String, Error>> {
+        let website_token = generate_website_token(
+            client.user_agent(),
+            client.language(),
+            token,
+            unix_epoch_secs(),
+        );
+        futures_util::future::ready(Ok(website_token)).boxed()
+    }
+}
+
+/// Generate website tokens by downloading the website's script and running it.
+///
+/// This is the default.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RemoteScriptWebsiteTokenProvider;
+
+impl WebsiteTokenProvider for RemoteScriptWebsiteTokenProvider {
+    fn generate<'a>(
+        &'a self,
+        client: &'a Client,
+        token: &'a str,
+    ) -> BoxFuture<'a, Result<String, Error>> {
+        async move {
+            let url = format!("{}/dist/js/wt.obf.js", client.website_base_url());
+            let script = client
+                .send(client.request(Method::GET, &url))
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+
+            let user_agent = client.user_agent().to_string();
+            let language = client.language().to_string();
+            let token = token.to_string();
+            tokio::task::spawn_blocking(move || eval_script(script, user_agent, language, token))
+                .await?
+        }
+        .boxed()
+    }
+}
+
+/// Generate website tokens by running a script from a local file.
+///
+/// The script must define a `generateWT` function,
+/// like the one served at `https://gofile.io/dist/js/wt.obf.js`.
+#[derive(Debug, Clone)]
+pub struct ScriptFileWebsiteTokenProvider {
+    path: PathBuf,
+}
+
+impl ScriptFileWebsiteTokenProvider {
+    /// Make a new provider that runs the script at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl WebsiteTokenProvider for ScriptFileWebsiteTokenProvider {
+    fn generate<'a>(
+        &'a self,
+        client: &'a Client,
+        token: &'a str,
+    ) -> BoxFuture<'a, Result<String, Error>> {
+        let path = self.path.clone();
+        let user_agent = client.user_agent().to_string();
+        let language = client.language().to_string();
+        let token = token.to_string();
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let script = std::fs::read_to_string(path)?;
+                eval_script(script, user_agent, language, token)
+            })
+            .await?
+        }
+        .boxed()
+    }
+}
+
+/// Always use the same website token.
+#[derive(Debug, Clone)]
+pub struct FixedWebsiteTokenProvider {
+    website_token: String,
+}
+
+impl FixedWebsiteTokenProvider {
+    /// Make a new provider that always returns the given website token.
+    pub fn new(website_token: impl Into<String>) -> Self {
+        Self {
+            website_token: website_token.into(),
+        }
+    }
+}
+
+impl WebsiteTokenProvider for FixedWebsiteTokenProvider {
+    fn generate<'a>(
+        &'a self,
+        _client: &'a Client,
+        _token: &'a str,
+    ) -> BoxFuture<'a, Result<String, Error>> {
+        futures_util::future::ready(Ok(self.website_token.clone())).boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::SCRIPT;
+
+    fn client() -> Client {
+        Client::builder()
+            .user_agent_and_language("test-agent", "test-lang")
+            .build()
+            .expect("failed to build client")
+    }
+
+    #[test]
+    fn native_algorithm() {
+        // 4 hour windows, so this is window 123.
+        let time_secs = f64::from(123 * 60 * 60 * 4 + 1);
+        let website_token =
+            generate_website_token("test-agent", "test-lang", "guest-token", time_secs);
+        assert_eq!(
+            website_token,
+            "348c964332640176de0cf2e46394b2b15833868f99bb81a3a739bd06956ae622"
+        );
+    }
+
+    #[tokio::test]
+    async fn script_file() {
+        let path = std::env::temp_dir().join(format!("gofile-wt-{}.js", std::process::id()));
+        std::fs::write(&path, SCRIPT).expect("failed to write script");
+
+        let client = client();
+        let provider = ScriptFileWebsiteTokenProvider::new(&path);
+        let website_token = provider.generate(&client, "guest-token").await;
+        std::fs::remove_file(&path).expect("failed to remove script");
+
+        assert_eq!(
+            website_token.expect("failed to generate website token"),
+            "test-agent|test-lang|guest-token"
+        );
+    }
+
+    #[tokio::test]
+    async fn fixed() {
+        let client = client();
+        let provider = FixedWebsiteTokenProvider::new("fixed");
+        let website_token = provider
+            .generate(&client, "guest-token")
+            .await
+            .expect("failed to generate website token");
+        assert_eq!(website_token, "fixed");
+    }
+}